@@ -3,8 +3,8 @@
 
 /*! Shared ABI definitions for Lottery Applications */
 
-use async_graphql::{Request, Response, SimpleObject};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ChainId, ContractAbi, ServiceAbi};
+use async_graphql::{InputObject, Request, Response, SimpleObject};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, BcsHashable, ChainId, ContractAbi, CryptoHash, ServiceAbi};
 use serde::{Deserialize, Serialize};
 
 // ========================================
@@ -81,16 +81,82 @@ pub enum RoundStatus {
     Active,   // Accepting ticket purchases
     Closed,   // Not accepting purchases, drawing winners
     Complete, // All winners drawn
+    Refunded, // Secret not revealed in time, tickets are paid back
 }
 
-/// Winner pool identifier
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
-pub enum WinnerPool {
-    Pool1,    // 15% of tickets, 20% of prize
-    Pool2,    // 7% of tickets, 25% of prize
-    Pool3,    // 5% of tickets, 30% of prize
-    Pool4,    // 3% of tickets, 25% of prize
-    Complete, // All winners drawn
+/// Basis points of 100%
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// A prize tier: its winners split `prize_bps` of the prize pool
+/// Tiers are drawn in order, so the first tier is drawn first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "PrizeTierInput")]
+pub struct PrizeTier {
+    pub winner_bps: u64,            // Share of the tickets that win in this tier, used without `winner_count`
+    pub winner_count: Option<u64>,  // Fixed number of winners
+    pub prize_bps: u64,             // Share of the prize pool
+}
+
+/// Tiers used when a round is created without any: 15/7/5/3% of tickets win 20/25/30/25% of the prize
+pub fn default_prize_tiers() -> Vec<PrizeTier> {
+    [(1_500, 2_000), (700, 2_500), (500, 3_000), (300, 2_500)]
+        .into_iter()
+        .map(|(winner_bps, prize_bps)| PrizeTier { winner_bps, winner_count: None, prize_bps })
+        .collect()
+}
+
+/// Check that tiers give away at most 100% of the tickets and of the prize pool, and that every tier has a winner
+pub fn validate_prize_tiers(tiers: &[PrizeTier]) -> Result<(), String> {
+    if tiers.is_empty() {
+        return Err("A round needs at least one prize tier".to_string());
+    }
+    if tiers.iter().any(|tier| tier.winner_count == Some(0)) {
+        return Err("A prize tier with a fixed winner count needs at least one winner".to_string());
+    }
+    tiers.iter()
+        .try_fold(0u64, |total, tier| total.checked_add(tier.winner_count.unwrap_or(1)))
+        .ok_or("Prize tiers have too many fixed winners")?;
+    let winner_bps: u64 = tiers.iter()
+        .filter(|tier| tier.winner_count.is_none())
+        .map(|tier| tier.winner_bps)
+        .sum();
+    if winner_bps > BASIS_POINTS {
+        return Err(format!("Prize tiers make {} bps of the tickets win, more than 100%", winner_bps));
+    }
+    let prize_bps: u64 = tiers.iter().map(|tier| tier.prize_bps).sum();
+    if prize_bps > BASIS_POINTS {
+        return Err(format!("Prize tiers share {} bps of the prize pool, more than 100%", prize_bps));
+    }
+    Ok(())
+}
+
+/// Winners a tier needs: its fixed winner count, or one
+fn required_tier_winners(tier: &PrizeTier) -> u64 {
+    tier.winner_count.unwrap_or(1)
+}
+
+/// Fewest tickets a round with these tiers can close with, so that every tier gets the winners it needs
+pub fn required_winners(tiers: &[PrizeTier]) -> u64 {
+    tiers.iter().map(required_tier_winners).fold(0, u64::saturating_add)
+}
+
+/// Number of winners of each tier for a round with `total_tickets` tickets
+/// With at least `required_winners` tickets, every tier gets its fixed winner count or at least one winner:
+/// a tier only takes the tickets left after setting aside the winners the later tiers need
+pub fn tier_winner_counts(tiers: &[PrizeTier], total_tickets: u64) -> Vec<u64> {
+    let mut tickets_left = total_tickets;
+    let mut reserved = required_winners(tiers);
+    tiers.iter()
+        .map(|tier| {
+            reserved = reserved.saturating_sub(required_tier_winners(tier));
+            let count = tier.winner_count
+                .unwrap_or((u128::from(total_tickets) * u128::from(tier.winner_bps) / u128::from(BASIS_POINTS)) as u64)
+                .max(1)
+                .min(tickets_left.saturating_sub(reserved));
+            tickets_left -= count;
+            count
+        })
+        .collect()
 }
 
 /// A lottery round
@@ -104,19 +170,17 @@ pub struct LotteryRound {
     pub total_tickets_sold: u64,
     pub next_ticket_number: u64,
     pub prize_pool: Amount,
-    pub current_winner_pool: WinnerPool,
     
-    // Winner pool sizes
-    pub pool1_count: u64,
-    pub pool2_count: u64,
-    pub pool3_count: u64,
-    pub pool4_count: u64,
+    // Prize tiers
+    pub prize_tiers: Vec<PrizeTier>,
+    pub current_tier: Option<u64>,       // Tier the next winner is drawn for (None once all are drawn)
+    pub tier_winner_counts: Vec<u64>,    // Calculated when the round closes
+    pub tier_winners_drawn: Vec<u64>,
     
-    // Winner pool progress
-    pub pool1_winners_drawn: u64,
-    pub pool2_winners_drawn: u64,
-    pub pool3_winners_drawn: u64,
-    pub pool4_winners_drawn: u64,
+    // Draw randomness (commit-reveal)
+    pub seed_commitment: Option<CryptoHash>, // Operator's commitment, see `seed_commitment`
+    pub entropy: CryptoHash,                 // Running hash of the ticket purchases
+    pub draw_seed: Option<CryptoHash>,       // Set once the secret is revealed
 }
 
 /// A user's ticket purchase
@@ -141,6 +205,25 @@ pub struct TicketPurchaseInfo {
     pub source_chain_id: Option<String>,
 }
 
+/// A contiguous range of tickets bought in one purchase
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, SimpleObject)]
+pub struct TicketRange {
+    pub first_ticket: u64,
+    pub last_ticket: u64,
+}
+
+/// All tickets an owner bought in a round, across purchases
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct UserTickets {
+    pub round_id: u64,
+    pub owner: AccountOwner,
+    pub total_tickets: u64,
+    pub amount_paid: Amount,
+    pub ranges: Vec<TicketRange>,  // One per purchase, in purchase order
+    pub ticket_numbers: Vec<u64>,
+    pub source_chain_id: Option<String>,
+}
+
 /// Winner information
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct LotteryWinnerInfo {
@@ -151,6 +234,30 @@ pub struct LotteryWinnerInfo {
     pub source_chain_id: Option<String>,
 }
 
+/// A winner recorded in a round summary
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct RoundWinnerSummary {
+    pub tier: u64,
+    pub ticket_number: u64,
+    pub owner: AccountOwner,
+    pub prize_amount: Amount,
+}
+
+/// Permanent record of a round, kept after its tickets, purchases and draw transcript are pruned
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct LotteryRoundSummary {
+    pub round_id: u64,
+    pub created_at: u64,
+    pub closed_at: Option<u64>,
+    pub ticket_price: Amount,
+    pub total_tickets_sold: u64,
+    pub prize_pool: Amount,
+    pub total_prizes: Amount,
+    pub seed_commitment: Option<CryptoHash>,
+    pub draw_seed: Option<CryptoHash>,
+    pub winners: Vec<RoundWinnerSummary>,  // In draw order
+}
+
 pub struct LotteryRoundsAbi;
 
 impl ContractAbi for LotteryRoundsAbi {
@@ -166,16 +273,59 @@ impl ServiceAbi for LotteryRoundsAbi {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LotteryRoundsParameters {
     pub native_app_id: ::linera_sdk::linera_base_types::ApplicationId,
+    /// Microbetreal router selling the tickets - prizes are then paid from its escrow instead of via lottery-app
+    #[serde(default)]
+    pub router_app_id: Option<::linera_sdk::linera_base_types::ApplicationId>,
+    /// Owner allowed to create and close rounds, commit draw seeds and draw winners
+    pub operator: AccountOwner,
+    /// Owner who adds a second secret to every draw, so that the operator alone cannot predict it
+    /// Without a co-signer, draws depend only on the operator's secret and the purchases
+    #[serde(default)]
+    pub cosigner: Option<AccountOwner>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum LotteryRoundsOperation {
     // Round management
-    CreateRound { ticket_price: Amount },
+    /// Create a round, optionally committing to the secret of its draw (see `seed_commitment`)
+    /// Without `prize_tiers`, the round uses `default_prize_tiers`
+    CreateRound {
+        ticket_price: Amount,
+        #[serde(default)]
+        seed_commitment: Option<CryptoHash>,
+        #[serde(default)]
+        prize_tiers: Vec<PrizeTier>,
+    },
+    /// Stop selling tickets in the active round (operator only)
     CloseRound,
-    GenerateWinner { round_id: u64 },
+    /// Draw the next winner of a closed round
+    /// The first draw reveals the operator's `secret`, which must match the round's commitment
+    GenerateWinner {
+        round_id: u64,
+        #[serde(default)]
+        secret: Option<String>,
+    },
+    /// Draw up to `max_count` winners of a closed round and pay them in one batch
+    /// Draws the same winners, in the same order, as repeated `GenerateWinner`s
+    DrawWinners {
+        round_id: u64,
+        max_count: u64,
+        #[serde(default)]
+        secret: Option<String>,
+    },
+    /// Commit to the secret of a round's draw (for rounds created without a commitment, before any ticket is sold)
+    CommitSeed { round_id: u64, seed_commitment: CryptoHash },
+    /// Commit to the co-signer's secret of a round's draw, before any ticket is sold (co-signer only)
+    CommitCosignerSeed { round_id: u64, seed_commitment: CryptoHash },
+    /// Reveal the co-signer's secret of a closed round, which the first draw waits for (co-signer only)
+    RevealCosignerSeed { round_id: u64, secret: String },
+    /// Pay back up to `max_count` ticket purchases of a round whose secret was not revealed
+    /// within `REVEAL_TIMEOUT_MICROS` of closing (anyone can call)
+    /// Rounds from before prize tiers cannot be drawn: unless complete, they are refunded at any time,
+    /// `max_count` tickets at a time, each ticket getting an equal share of what is left of the prize pool
+    RefundRound { round_id: u64, max_count: u64 },
     
-    // Ticket purchase (called by lottery-app)
+    // Ticket purchase (called by Microbetreal when `router_app_id` is set, otherwise by lottery-app)
     PurchaseTickets {
         owner: AccountOwner,
         amount: Amount,
@@ -183,8 +333,11 @@ pub enum LotteryRoundsOperation {
         source_chain_id: Option<String>,
     },
     
-    // Configuration
+    // Configuration (operator only)
     SetLotteryAppId { lottery_app_id: String },
+    
+    /// Prune up to `max_entries` entries of rounds past the history limit (their summaries are kept, operator only)
+    CleanupRounds { max_entries: u64 },
 
     // Queries
     GetActiveRound,
@@ -193,6 +346,7 @@ pub enum LotteryRoundsOperation {
     GetRoundTicketPurchases { round_id: u64 },
     GetUserTickets { round_id: u64, owner: AccountOwner },
     GetRoundWinners { round_id: u64 },
+    GetRoundSummary { round_id: u64 },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -213,9 +367,417 @@ pub enum LotteryRoundsResponse {
         new_round_created: bool,
         source_chain_id: Option<String>,
     },
+    WinnersDrawn {
+        round_id: u64,
+        winners: Vec<LotteryWinnerInfo>,
+        new_round_created: bool,
+    },
+    UserTickets(UserTickets),
+    RoundSummary(Option<LotteryRoundSummary>),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum LotteryRoundsMessage {
     Notify,
 }
+
+// ========================================
+// Draw randomness (commit-reveal)
+// ========================================
+//
+// The operator commits to `seed_commitment(round_id, secret)` before the first ticket is sold.
+// Every ticket purchase is mixed into the round's entropy, starting from `initial_entropy`.
+// The secret is revealed at the first draw: the draw seed is `draw_seed(round_id, secret, entropy, cosigner_secret)`,
+// and the n-th winner (from 0) is picked with `draw_value(seed, n)`.
+// The buyers cannot steer the seed, since they cannot see the secret. The operator knows its secret
+// while tickets are sold, and could pick its own purchases or the closing moment to steer the seed.
+// When a co-signer is configured, it commits to a second secret (`cosigner_commitment`) before the first
+// sale and reveals it only after the round is closed, so the operator cannot compute the seed in time.
+// Anyone can recompute the seed once the secrets are public.
+// If a secret is not revealed within `REVEAL_TIMEOUT_MICROS` of closing, the round is refunded instead,
+// so withholding a secret can cancel a round but not choose its winners.
+
+/// Time the operator has to reveal the secret of a closed round (24 hours)
+pub const REVEAL_TIMEOUT_MICROS: u64 = 24 * 60 * 60 * 1_000_000;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SeedSecret {
+    round_id: u64,
+    secret: String,
+}
+
+impl BcsHashable<'_> for SeedSecret {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CosignerSecret {
+    round_id: u64,
+    secret: String,
+}
+
+impl BcsHashable<'_> for CosignerSecret {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RoundEntropy {
+    round_id: u64,
+    created_at: u64,
+}
+
+impl BcsHashable<'_> for RoundEntropy {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PurchaseEntropy {
+    previous: CryptoHash,
+    owner: AccountOwner,
+    amount: Amount,
+    first_ticket: u64,
+    last_ticket: u64,
+}
+
+impl BcsHashable<'_> for PurchaseEntropy {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DrawSeed {
+    round_id: u64,
+    secret: String,
+    entropy: CryptoHash,
+    cosigner_secret: Option<String>,
+}
+
+impl BcsHashable<'_> for DrawSeed {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DrawStep {
+    seed: CryptoHash,
+    draw_index: u64,
+}
+
+impl BcsHashable<'_> for DrawStep {}
+
+/// Commitment to the secret of a round's draw
+pub fn seed_commitment(round_id: u64, secret: &str) -> CryptoHash {
+    CryptoHash::new(&SeedSecret { round_id, secret: secret.to_string() })
+}
+
+/// Commitment to the co-signer's secret of a round's draw
+pub fn cosigner_commitment(round_id: u64, secret: &str) -> CryptoHash {
+    CryptoHash::new(&CosignerSecret { round_id, secret: secret.to_string() })
+}
+
+/// Entropy of a round before any ticket is sold
+pub fn initial_entropy(round_id: u64, created_at: u64) -> CryptoHash {
+    CryptoHash::new(&RoundEntropy { round_id, created_at })
+}
+
+/// Mix a ticket purchase into the entropy of its round
+pub fn mix_purchase_entropy(previous: CryptoHash, owner: AccountOwner, amount: Amount, first_ticket: u64, last_ticket: u64) -> CryptoHash {
+    CryptoHash::new(&PurchaseEntropy { previous, owner, amount, first_ticket, last_ticket })
+}
+
+/// Seed all winners of a round are derived from
+pub fn draw_seed(round_id: u64, secret: &str, entropy: CryptoHash, cosigner_secret: Option<&str>) -> CryptoHash {
+    CryptoHash::new(&DrawSeed {
+        round_id,
+        secret: secret.to_string(),
+        entropy,
+        cosigner_secret: cosigner_secret.map(str::to_string),
+    })
+}
+
+/// Random value of the `draw_index`-th draw of a round
+pub fn draw_value(seed: CryptoHash, draw_index: u64) -> u64 {
+    <[u64; 4]>::from(CryptoHash::new(&DrawStep { seed, draw_index }))[0]
+}
+
+/// Position picked by the `draw_index`-th draw (from 0) in a shuffle of the round's tickets (Fisher-Yates)
+/// Positions `0..draw_index` hold the tickets already drawn, so the pick is among the tickets left
+/// and never needs a retry. Position `p` initially holds ticket `p + 1`; after each draw the picked
+/// position takes the ticket at position `draw_index`.
+/// The pick reduces a 64-bit value modulo the number of tickets left, so it is not exactly uniform:
+/// each ticket's chance is off by less than 2^-64, a relative bias below (tickets left) / 2^64.
+/// Returns None once every ticket is drawn (`draw_index >= total_tickets`)
+pub fn draw_position(value: u64, draw_index: u64, total_tickets: u64) -> Option<u64> {
+    let tickets_left = total_tickets.checked_sub(draw_index).filter(|left| *left > 0)?;
+    Some(draw_index + value % tickets_left)
+}
+
+// ========================================
+// Draw transcript and verification
+// ========================================
+
+/// A ticket purchase, in the order it was mixed into the entropy of its round
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct PurchaseRecord {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub first_ticket: u64,
+    pub last_ticket: u64,
+}
+
+/// One winner draw
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DrawRecord {
+    pub draw_index: u64,
+    pub value: u64,     // draw_value(draw_seed, draw_index)
+    pub position: u64,  // draw_position(value, draw_index, total_tickets)
+    pub ticket_number: u64,
+    pub owner: AccountOwner,
+}
+
+/// Everything needed to recompute the winners of a round offline (see `verify_draw_proof`)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DrawProof {
+    pub round_id: u64,
+    pub created_at: u64,
+    pub seed_commitment: Option<CryptoHash>,
+    pub purchases: Vec<PurchaseRecord>,
+    pub entropy: CryptoHash,
+    pub secret: Option<String>,       // Revealed at the first draw
+    pub cosigner_commitment: Option<CryptoHash>,
+    pub cosigner_secret: Option<String>,  // Revealed after the round closed
+    pub draw_seed: Option<CryptoHash>,
+    pub total_tickets: u64,
+    pub draws: Vec<DrawRecord>,
+}
+
+/// Recompute a round's entropy, seed and winners from its transcript and check them against it
+pub fn verify_draw_proof(proof: &DrawProof) -> Result<(), String> {
+    let mut entropy = initial_entropy(proof.round_id, proof.created_at);
+    let mut next_ticket = 1;
+    for purchase in &proof.purchases {
+        if purchase.first_ticket != next_ticket || purchase.last_ticket < purchase.first_ticket {
+            return Err(format!("Purchase of tickets {}-{} breaks the ticket sequence", purchase.first_ticket, purchase.last_ticket));
+        }
+        next_ticket = purchase.last_ticket + 1;
+        entropy = mix_purchase_entropy(entropy, purchase.owner, purchase.amount, purchase.first_ticket, purchase.last_ticket);
+    }
+    if entropy != proof.entropy {
+        return Err("Entropy does not match the purchases".to_string());
+    }
+    if next_ticket - 1 != proof.total_tickets {
+        return Err("Ticket count does not match the purchases".to_string());
+    }
+    if proof.draws.is_empty() {
+        return Ok(());
+    }
+    
+    let secret = proof.secret.as_deref().ok_or("Draws were made without revealing the secret")?;
+    if proof.seed_commitment != Some(seed_commitment(proof.round_id, secret)) {
+        return Err("Secret does not match the seed commitment".to_string());
+    }
+    let cosigner_secret = match proof.cosigner_commitment {
+        Some(commitment) => {
+            let cosigner_secret = proof.cosigner_secret.as_deref().ok_or("Draws were made without the co-signer's secret")?;
+            if commitment != cosigner_commitment(proof.round_id, cosigner_secret) {
+                return Err("Co-signer's secret does not match its commitment".to_string());
+            }
+            Some(cosigner_secret)
+        }
+        None => None,
+    };
+    let seed = draw_seed(proof.round_id, secret, entropy, cosigner_secret);
+    if proof.draw_seed != Some(seed) {
+        return Err("Draw seed does not match the secrets and entropy".to_string());
+    }
+    
+    let mut shuffled = std::collections::HashMap::new();
+    for (draw_index, draw) in proof.draws.iter().enumerate() {
+        let draw_index = draw_index as u64;
+        let value = draw_value(seed, draw_index);
+        let position = draw_position(value, draw_index, proof.total_tickets).ok_or("More draws than tickets")?;
+        let ticket_number = shuffled.get(&position).copied().unwrap_or(position + 1);
+        let replacement = shuffled.get(&draw_index).copied().unwrap_or(draw_index + 1);
+        shuffled.insert(position, replacement);
+        if draw.draw_index != draw_index || draw.value != value || draw.position != position || draw.ticket_number != ticket_number {
+            return Err(format!("Draw {} does not match the seed", draw_index));
+        }
+        let owner = proof.purchases.iter()
+            .find(|purchase| purchase.first_ticket <= draw.ticket_number && draw.ticket_number <= purchase.last_ticket)
+            .map(|purchase| purchase.owner);
+        if owner != Some(draw.owner) {
+            return Err(format!("Ticket {} does not belong to the recorded winner", draw.ticket_number));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUND_ID: u64 = 7;
+    const CREATED_AT: u64 = 1_000;
+    const SECRET: &str = "operator secret";
+    const COSIGNER_SECRET: &str = "co-signer secret";
+
+    fn owner(byte: u8) -> AccountOwner {
+        AccountOwner::Address20([byte; 20])
+    }
+
+    /// Transcript of a round with three purchases and `draw_count` draws, shuffled with a plain vector
+    fn sample_proof(draw_count: u64) -> DrawProof {
+        let purchases = vec![
+            PurchaseRecord { owner: owner(1), amount: Amount::from_tokens(3), first_ticket: 1, last_ticket: 3 },
+            PurchaseRecord { owner: owner(2), amount: Amount::from_tokens(1), first_ticket: 4, last_ticket: 4 },
+            PurchaseRecord { owner: owner(3), amount: Amount::from_tokens(2), first_ticket: 5, last_ticket: 6 },
+        ];
+        let mut entropy = initial_entropy(ROUND_ID, CREATED_AT);
+        for purchase in &purchases {
+            entropy = mix_purchase_entropy(entropy, purchase.owner, purchase.amount, purchase.first_ticket, purchase.last_ticket);
+        }
+        let total_tickets = 6;
+        let seed = draw_seed(ROUND_ID, SECRET, entropy, Some(COSIGNER_SECRET));
+        
+        let mut tickets: Vec<u64> = (1..=total_tickets).collect();
+        let draws = (0..draw_count).map(|draw_index| {
+            let value = draw_value(seed, draw_index);
+            let position = draw_position(value, draw_index, total_tickets).unwrap();
+            tickets.swap(draw_index as usize, position as usize);
+            let ticket_number = tickets[draw_index as usize];
+            let owner = purchases.iter()
+                .find(|purchase| purchase.first_ticket <= ticket_number && ticket_number <= purchase.last_ticket)
+                .unwrap()
+                .owner;
+            DrawRecord { draw_index, value, position, ticket_number, owner }
+        }).collect();
+        
+        DrawProof {
+            round_id: ROUND_ID,
+            created_at: CREATED_AT,
+            seed_commitment: Some(seed_commitment(ROUND_ID, SECRET)),
+            purchases,
+            entropy,
+            secret: Some(SECRET.to_string()),
+            cosigner_commitment: Some(cosigner_commitment(ROUND_ID, COSIGNER_SECRET)),
+            cosigner_secret: Some(COSIGNER_SECRET.to_string()),
+            draw_seed: Some(seed),
+            total_tickets,
+            draws,
+        }
+    }
+
+    #[test]
+    fn test_verify_draw_proof_round_trip() {
+        assert_eq!(verify_draw_proof(&sample_proof(0)), Ok(()));
+        assert_eq!(verify_draw_proof(&sample_proof(3)), Ok(()));
+        assert_eq!(verify_draw_proof(&sample_proof(6)), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_draw_proof_rejects_tampering() {
+        let mut proof = sample_proof(3);
+        proof.purchases[1].amount = Amount::from_tokens(2);
+        assert!(verify_draw_proof(&proof).is_err(), "changed purchase amount");
+
+        let mut proof = sample_proof(3);
+        proof.purchases[2].first_ticket = 6;
+        assert!(verify_draw_proof(&proof).is_err(), "gap in the ticket sequence");
+
+        let mut proof = sample_proof(3);
+        proof.total_tickets = 7;
+        assert!(verify_draw_proof(&proof).is_err(), "changed ticket count");
+
+        let mut proof = sample_proof(3);
+        proof.secret = Some("another secret".to_string());
+        assert!(verify_draw_proof(&proof).is_err(), "secret not matching the commitment");
+
+        let mut proof = sample_proof(3);
+        proof.secret = None;
+        assert!(verify_draw_proof(&proof).is_err(), "draws without a revealed secret");
+
+        let mut proof = sample_proof(3);
+        proof.cosigner_secret = Some("another secret".to_string());
+        assert!(verify_draw_proof(&proof).is_err(), "co-signer's secret not matching its commitment");
+
+        let mut proof = sample_proof(3);
+        proof.cosigner_commitment = None;
+        assert!(verify_draw_proof(&proof).is_err(), "co-signer's secret left out of the commitment check");
+
+        let mut proof = sample_proof(3);
+        proof.draw_seed = Some(initial_entropy(ROUND_ID, CREATED_AT));
+        assert!(verify_draw_proof(&proof).is_err(), "changed draw seed");
+
+        let mut proof = sample_proof(3);
+        proof.draws[1].ticket_number = proof.draws[0].ticket_number;
+        assert!(verify_draw_proof(&proof).is_err(), "changed winning ticket");
+
+        let mut proof = sample_proof(3);
+        proof.draws.swap(0, 1);
+        assert!(verify_draw_proof(&proof).is_err(), "reordered draws");
+
+        let mut proof = sample_proof(3);
+        proof.draws[2].owner = owner(9);
+        assert!(verify_draw_proof(&proof).is_err(), "changed winner");
+    }
+
+    #[test]
+    fn test_draw_position_stays_in_tickets_left() {
+        let total_tickets = 10;
+        for draw_index in 0..total_tickets {
+            for value in [0, 1, 9, 10, u64::MAX - 1, u64::MAX] {
+                let position = draw_position(value, draw_index, total_tickets).unwrap();
+                assert!(draw_index <= position && position < total_tickets, "value {} at draw {} picked {}", value, draw_index, position);
+            }
+        }
+        // The last draw has a single ticket left
+        assert_eq!(draw_position(u64::MAX, total_tickets - 1, total_tickets), Some(total_tickets - 1));
+        // Nothing is left to draw once every ticket is drawn
+        assert_eq!(draw_position(0, total_tickets, total_tickets), None);
+        assert_eq!(draw_position(0, total_tickets + 1, total_tickets), None);
+        assert_eq!(draw_position(0, 0, 0), None);
+    }
+
+    #[test]
+    fn test_draw_position_draws_every_ticket_once() {
+        let seed = draw_seed(ROUND_ID, SECRET, initial_entropy(ROUND_ID, CREATED_AT), None);
+        let total_tickets = 50;
+        let mut tickets: Vec<u64> = (1..=total_tickets).collect();
+        for draw_index in 0..total_tickets {
+            let position = draw_position(draw_value(seed, draw_index), draw_index, total_tickets).unwrap();
+            tickets.swap(draw_index as usize, position as usize);
+        }
+        let mut drawn = tickets.clone();
+        drawn.sort();
+        assert_eq!(drawn, (1..=total_tickets).collect::<Vec<_>>());
+    }
+
+    fn tier(winner_bps: u64, winner_count: Option<u64>, prize_bps: u64) -> PrizeTier {
+        PrizeTier { winner_bps, winner_count, prize_bps }
+    }
+
+    #[test]
+    fn test_validate_prize_tiers() {
+        assert_eq!(validate_prize_tiers(&default_prize_tiers()), Ok(()));
+        assert_eq!(validate_prize_tiers(&[tier(0, Some(1), 5_000), tier(5_000, None, 5_000)]), Ok(()));
+        
+        assert!(validate_prize_tiers(&[]).is_err(), "no tier");
+        assert!(validate_prize_tiers(&[tier(6_000, None, 5_000), tier(5_000, None, 5_000)]).is_err(), "more than 100% of the tickets win");
+        assert!(validate_prize_tiers(&[tier(1_000, None, 6_000), tier(1_000, None, 5_000)]).is_err(), "more than 100% of the prize pool");
+        assert!(validate_prize_tiers(&[tier(0, Some(0), 5_000)]).is_err(), "fixed tier without winners");
+        assert!(validate_prize_tiers(&[tier(0, Some(u64::MAX), 5_000), tier(0, Some(1), 5_000)]).is_err(), "fixed winner counts overflow");
+    }
+
+    #[test]
+    fn test_required_winners() {
+        assert_eq!(required_winners(&default_prize_tiers()), 4);
+        assert_eq!(required_winners(&[tier(0, Some(3), 5_000), tier(1_000, None, 2_500), tier(0, Some(2), 2_500)]), 6);
+    }
+
+    #[test]
+    fn test_tier_winner_counts() {
+        // Shares of the tickets, rounded down
+        assert_eq!(tier_winner_counts(&default_prize_tiers(), 100), vec![15, 7, 5, 3]);
+        // Small rounds still give every tier a winner
+        assert_eq!(tier_winner_counts(&default_prize_tiers(), 4), vec![1, 1, 1, 1]);
+        assert_eq!(tier_winner_counts(&default_prize_tiers(), 10), vec![1, 1, 1, 1]);
+        
+        // Fixed counts are kept for later tiers even when an earlier tier wants every ticket
+        let tiers = [tier(10_000, None, 5_000), tier(0, Some(2), 3_000), tier(0, Some(1), 2_000)];
+        assert_eq!(tier_winner_counts(&tiers, 4), vec![1, 2, 1]);
+        assert_eq!(tier_winner_counts(&tiers, 10), vec![7, 2, 1]);
+        for total_tickets in required_winners(&tiers)..20 {
+            let counts = tier_winner_counts(&tiers, total_tickets);
+            assert!(counts.iter().all(|count| *count > 0), "a tier without winners at {} tickets", total_tickets);
+            assert!(counts.iter().sum::<u64>() <= total_tickets, "more winners than tickets at {} tickets", total_tickets);
+        }
+    }
+}
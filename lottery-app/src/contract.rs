@@ -182,10 +182,11 @@ impl Contract for LotteryAppContract {
                 let _native_response: native::NativeResponse = self.runtime.call_application(
                     true,
                     native_app_id,
-                    &native::NativeOperation::Transfer {
+                    &native::NativeOperation::TransferWithMemo {
                         owner: payer,
                         amount,
                         target_account,
+                        memo: Some("lottery:prize".to_string()),
                     },
                 );
 
@@ -6,18 +6,19 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::{Amount, ApplicationId, WithContractAbi},
+    linera_base_types::{AccountOwner, Amount, ApplicationId, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 use lottery_abi::{
     LotteryRoundsAbi, LotteryRoundsOperation, LotteryRoundsResponse, LotteryRoundsMessage as Message,
-    LotteryRound as LibLotteryRound, RoundStatus as LibRoundStatus, WinnerPool as LibWinnerPool,
+    LotteryRound as LibLotteryRound, RoundStatus as LibRoundStatus,
     TicketPurchase as LibTicketPurchase, TicketPurchaseInfo as LibTicketPurchaseInfo,
-    LotteryWinnerInfo as LibLotteryWinnerInfo,
+    LotteryWinnerInfo as LibLotteryWinnerInfo, UserTickets as LibUserTickets,
     LotteryAppAbi, LotteryAppOperation, LotteryAppResponse,
 };
-use self::state::{LotteryRoundsState, LotteryRound, RoundStatus, WinnerPool, TicketPurchase};
+use native_fungible_abi::{ExtendedNativeFungibleTokenAbi, ExtendedOperation, ExtendedResponse};
+use self::state::{LotteryRoundsState, LotteryRound, RoundStatus, TicketPurchase, OwnerTickets};
 
 
 // Conversion functions between lib types and state types
@@ -26,16 +27,7 @@ fn round_status_to_lib(status: RoundStatus) -> LibRoundStatus {
         RoundStatus::Active => LibRoundStatus::Active,
         RoundStatus::Closed => LibRoundStatus::Closed,
         RoundStatus::Complete => LibRoundStatus::Complete,
-    }
-}
-
-fn winner_pool_to_lib(pool: WinnerPool) -> LibWinnerPool {
-    match pool {
-        WinnerPool::Pool1 => LibWinnerPool::Pool1,
-        WinnerPool::Pool2 => LibWinnerPool::Pool2,
-        WinnerPool::Pool3 => LibWinnerPool::Pool3,
-        WinnerPool::Pool4 => LibWinnerPool::Pool4,
-        WinnerPool::Complete => LibWinnerPool::Complete,
+        RoundStatus::Refunded => LibRoundStatus::Refunded,
     }
 }
 
@@ -49,15 +41,13 @@ fn lottery_round_to_lib(round: LotteryRound) -> LibLotteryRound {
         total_tickets_sold: round.total_tickets_sold,
         next_ticket_number: round.next_ticket_number,
         prize_pool: round.prize_pool,
-        current_winner_pool: winner_pool_to_lib(round.current_winner_pool),
-        pool1_count: round.pool1_count,
-        pool2_count: round.pool2_count,
-        pool3_count: round.pool3_count,
-        pool4_count: round.pool4_count,
-        pool1_winners_drawn: round.pool1_winners_drawn,
-        pool2_winners_drawn: round.pool2_winners_drawn,
-        pool3_winners_drawn: round.pool3_winners_drawn,
-        pool4_winners_drawn: round.pool4_winners_drawn,
+        prize_tiers: round.prize_tiers,
+        current_tier: round.current_tier,
+        tier_winner_counts: round.tier_winner_counts,
+        tier_winners_drawn: round.tier_winners_drawn,
+        seed_commitment: round.seed_commitment,
+        entropy: round.entropy,
+        draw_seed: round.draw_seed,
     }
 }
 
@@ -80,6 +70,21 @@ fn ticket_purchase_to_lib(purchase: TicketPurchase) -> LibTicketPurchase {
     }
 }
 
+fn owner_tickets_to_lib(round_id: u64, owner_tickets: OwnerTickets) -> LibUserTickets {
+    let ticket_numbers = owner_tickets.ranges.iter()
+        .flat_map(|range| range.first_ticket..=range.last_ticket)
+        .collect();
+    LibUserTickets {
+        round_id,
+        owner: owner_tickets.owner,
+        total_tickets: owner_tickets.total_tickets,
+        amount_paid: owner_tickets.amount_paid,
+        ranges: owner_tickets.ranges,
+        ticket_numbers,
+        source_chain_id: owner_tickets.source_chain_id,
+    }
+}
+
 pub struct LotteryRoundsContract {
     state: LotteryRoundsState,
     runtime: ContractRuntime<Self>,
@@ -112,6 +117,8 @@ impl Contract for LotteryRoundsContract {
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
             LotteryRoundsOperation::SetLotteryAppId { lottery_app_id } => {
+                // The lottery app is trusted to sell tickets, so only the operator can choose it
+                self.check_operator();
                 match lottery_app_id.parse::<ApplicationId>() {
                     Ok(app_id) => {
                         let typed_app_id: ApplicationId<LotteryAppAbi> = app_id.with_abi();
@@ -123,15 +130,42 @@ impl Contract for LotteryRoundsContract {
             }
 
 
-            LotteryRoundsOperation::CreateRound { ticket_price } => {
+            LotteryRoundsOperation::CreateRound { ticket_price, seed_commitment, prize_tiers } => {
+                self.check_operator();
                 let timestamp = self.runtime.system_time().micros();
-                match self.state.create_lottery_round(ticket_price, timestamp).await {
+                match self.state.create_lottery_round(ticket_price, timestamp, seed_commitment, prize_tiers).await {
                     Ok(round_id) => LotteryRoundsResponse::RoundId(round_id),
                     Err(e) => panic!("Failed to create lottery round: {}", e),
                 }
             }
             
+            LotteryRoundsOperation::CommitSeed { round_id, seed_commitment } => {
+                self.check_operator();
+                match self.state.commit_seed(round_id, seed_commitment).await {
+                    Ok(()) => LotteryRoundsResponse::Ok,
+                    Err(e) => panic!("Failed to commit seed: {}", e),
+                }
+            }
+            
+            LotteryRoundsOperation::CommitCosignerSeed { round_id, seed_commitment } => {
+                self.check_cosigner();
+                match self.state.commit_cosigner_seed(round_id, seed_commitment).await {
+                    Ok(()) => LotteryRoundsResponse::Ok,
+                    Err(e) => panic!("Failed to commit co-signer seed: {}", e),
+                }
+            }
+            
+            LotteryRoundsOperation::RevealCosignerSeed { round_id, secret } => {
+                self.check_cosigner();
+                let timestamp = self.runtime.system_time().micros();
+                match self.state.reveal_cosigner_seed(round_id, secret, timestamp).await {
+                    Ok(()) => LotteryRoundsResponse::Ok,
+                    Err(e) => panic!("Failed to reveal co-signer seed: {}", e),
+                }
+            }
+            
             LotteryRoundsOperation::CloseRound => {
+                self.check_operator();
                 let timestamp = self.runtime.system_time().micros();
                 match self.state.close_lottery_round(timestamp).await {
                     Ok(round_id) => LotteryRoundsResponse::RoundId(round_id),
@@ -139,44 +173,26 @@ impl Contract for LotteryRoundsContract {
                 }
             }
             
-            LotteryRoundsOperation::GenerateWinner { round_id } => {
-                // Generate VRF value automatically from timestamp + block height
+            LotteryRoundsOperation::GenerateWinner { round_id, secret } => {
+                self.check_operator();
                 let timestamp = self.runtime.system_time().micros();
-                let block_height = self.runtime.block_height();
-                let vrf_value = timestamp.wrapping_add(block_height.into());
                 
-                eprintln!("GenerateWinner: round_id={}, vrf_value={} (timestamp={}, block={})", 
-                    round_id, vrf_value, timestamp, block_height);
+                // Winners are derived from the committed secret and the round's purchases
+                let draw_seed = match self.state.reveal_draw_seed(round_id, secret, timestamp).await {
+                    Ok(draw_seed) => draw_seed,
+                    Err(e) => panic!("Failed to reveal draw seed: {}", e),
+                };
+                
+                eprintln!("GenerateWinner: round_id={}, draw_seed={}", round_id, draw_seed);
                 
                 // Get default ticket price for new rounds
                 let default_ticket_price = self.state.get_current_ticket_price().await
                     .unwrap_or(Amount::from_tokens(1));
                 
-                // Generate one winner using VRF
-                match self.state.generate_winner(vrf_value, round_id, timestamp, default_ticket_price).await {
+                // Generate one winner from the draw seed
+                match self.state.generate_winner(draw_seed, round_id, timestamp, default_ticket_price).await {
                     Ok((round_id, ticket_number, owner, prize_amount, new_round_created, source_chain_id)) => {
-                        // Get lottery app ID from state (set via SetLotteryAppId operation)
-                        let lottery_app_id = self.state.lottery_app_id.get()
-                            .expect("Lottery app ID not set - run SetLotteryAppId first");
-                        
-                        // Call lottery-app to send prize
-                        if prize_amount > Amount::ZERO {
-                            let _response: LotteryAppResponse = self.runtime.call_application(
-                                true, // authenticated
-                                lottery_app_id,
-                                &LotteryAppOperation::SendPrize {
-                                    recipient: owner.clone(),
-                                    amount: prize_amount,
-                                    source_chain_id: source_chain_id.clone(),
-                                },
-                            );
-                            
-                            // Mark prize as claimed
-                            if let Err(e) = self.state.mark_prize_claimed(round_id, ticket_number).await {
-                                eprintln!("Failed to mark prize as claimed: {}", e);
-                            }
-                        }
-
+                        self.pay_prizes(round_id, vec![(ticket_number, owner, prize_amount, source_chain_id.clone())]).await;
                         
                         LotteryRoundsResponse::WinnerGenerated {
                             round_id,
@@ -191,8 +207,56 @@ impl Contract for LotteryRoundsContract {
                 }
             }
 
+            LotteryRoundsOperation::DrawWinners { round_id, max_count, secret } => {
+                self.check_operator();
+                let timestamp = self.runtime.system_time().micros();
+                let draw_seed = match self.state.reveal_draw_seed(round_id, secret, timestamp).await {
+                    Ok(draw_seed) => draw_seed,
+                    Err(e) => panic!("Failed to reveal draw seed: {}", e),
+                };
+                let default_ticket_price = self.state.get_current_ticket_price().await
+                    .unwrap_or(Amount::from_tokens(1));
+                
+                match self.state.draw_winners(draw_seed, round_id, max_count, timestamp, default_ticket_price).await {
+                    Ok((winners, new_round_created)) => {
+                        eprintln!("DrawWinners: round_id={}, drew {} winners", round_id, winners.len());
+                        self.pay_prizes(round_id, winners.clone()).await;
+                        
+                        let winners = winners.into_iter()
+                            .map(|(ticket_number, owner, prize_amount, source_chain_id)| LibLotteryWinnerInfo {
+                                ticket_number,
+                                owner,
+                                prize_amount,
+                                claimed: prize_amount > Amount::ZERO,
+                                source_chain_id,
+                            })
+                            .collect();
+                        LotteryRoundsResponse::WinnersDrawn {
+                            round_id,
+                            winners,
+                            new_round_created,
+                        }
+                    }
+                    Err(e) => panic!("Failed to draw winners: {}", e),
+                }
+            }
+
+            LotteryRoundsOperation::RefundRound { round_id, max_count } => {
+                let timestamp = self.runtime.system_time().micros();
+                match self.state.refund_round(round_id, max_count, timestamp).await {
+                    Ok(refunds) => {
+                        eprintln!("RefundRound: round_id={}, refunded {} purchases", round_id, refunds.len());
+                        self.send_payouts(round_id, refunds, "lottery:refund");
+                        LotteryRoundsResponse::Ok
+                    }
+                    Err(e) => panic!("Failed to refund round: {}", e),
+                }
+            }
+
             LotteryRoundsOperation::PurchaseTickets { owner, amount, ticket_price, source_chain_id } => {
-                match self.state.purchase_tickets(owner, amount, ticket_price, source_chain_id).await {
+                self.check_ticket_seller();
+                let cosigned = self.runtime.application_parameters().cosigner.is_some();
+                match self.state.purchase_tickets(owner, amount, ticket_price, source_chain_id, cosigned).await {
                     Ok(purchase) => LotteryRoundsResponse::TicketPurchase(ticket_purchase_to_lib(purchase)),
                     Err(e) => panic!("Failed to purchase tickets: {}", e),
                 }
@@ -249,12 +313,30 @@ impl Contract for LotteryRoundsContract {
             
             LotteryRoundsOperation::GetUserTickets { round_id, owner } => {
                 match self.state.get_user_tickets(round_id, owner).await {
-                    Ok(Some(purchase)) => LotteryRoundsResponse::TicketPurchase(ticket_purchase_to_lib(purchase)),
+                    Ok(Some(owner_tickets)) => LotteryRoundsResponse::UserTickets(owner_tickets_to_lib(round_id, owner_tickets)),
                     Ok(None) => panic!("No tickets found for user"),
                     Err(e) => panic!("Failed to get user tickets: {}", e),
                 }
             }
             
+            LotteryRoundsOperation::CleanupRounds { max_entries } => {
+                self.check_operator();
+                match self.state.cleanup_rounds(max_entries).await {
+                    Ok(removed) => {
+                        eprintln!("Removed {} entries of old rounds", removed);
+                        LotteryRoundsResponse::Ok
+                    }
+                    Err(e) => panic!("Failed to clean up rounds: {}", e),
+                }
+            }
+            
+            LotteryRoundsOperation::GetRoundSummary { round_id } => {
+                match self.state.get_round_summary(round_id).await {
+                    Ok(summary) => LotteryRoundsResponse::RoundSummary(summary),
+                    Err(e) => panic!("Failed to get round summary: {}", e),
+                }
+            }
+            
             LotteryRoundsOperation::GetRoundWinners { round_id } => {
                 match self.state.get_round_winners(round_id).await {
                     Ok(winners) => {
@@ -284,3 +366,111 @@ impl Contract for LotteryRoundsContract {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl LotteryRoundsContract {
+    /// Only the configured operator manages rounds and their draws
+    fn check_operator(&mut self) {
+        let operator = self.runtime.application_parameters().operator;
+        assert_eq!(
+            self.runtime.authenticated_signer(),
+            Some(operator),
+            "Only the lottery operator can manage rounds"
+        );
+    }
+
+    /// Only the application holding the ticket payments can record purchases:
+    /// Microbetreal when `router_app_id` is set, otherwise the lottery app
+    fn check_ticket_seller(&mut self) {
+        let seller = match self.runtime.application_parameters().router_app_id {
+            Some(router_app_id) => router_app_id,
+            None => self.state.lottery_app_id.get()
+                .expect("Lottery app ID not set - run SetLotteryAppId first")
+                .forget_abi(),
+        };
+        assert_eq!(
+            self.runtime.authenticated_caller_id(),
+            Some(seller),
+            "Tickets can only be purchased through the lottery app or Microbetreal"
+        );
+    }
+
+    /// Only the configured co-signer commits to and reveals the second secret of a draw
+    fn check_cosigner(&mut self) {
+        let cosigner = self.runtime.application_parameters().cosigner
+            .expect("No co-signer is configured");
+        assert_eq!(
+            self.runtime.authenticated_signer(),
+            Some(cosigner),
+            "Only the co-signer can commit to and reveal its secret"
+        );
+    }
+
+    /// Pay drawn winners their prizes and mark them as claimed
+    /// `winners` holds (ticket_number, owner, prize_amount, source_chain_id)
+    async fn pay_prizes(&mut self, round_id: u64, winners: Vec<(u64, AccountOwner, Amount, Option<String>)>) {
+        let winners: Vec<_> = winners.into_iter()
+            .filter(|(_, _, prize_amount, _)| *prize_amount > Amount::ZERO)
+            .collect();
+        if winners.is_empty() {
+            return;
+        }
+        
+        let payouts = winners.iter()
+            .map(|(_, owner, prize_amount, source_chain_id)| (*owner, *prize_amount, source_chain_id.clone()))
+            .collect();
+        self.send_payouts(round_id, payouts, "lottery:prize");
+        
+        // Mark prizes as claimed
+        for (ticket_number, _, _, _) in winners {
+            if let Err(e) = self.state.mark_prize_claimed(round_id, ticket_number).await {
+                eprintln!("Failed to mark prize as claimed: {}", e);
+            }
+        }
+    }
+    
+    /// Send `(owner, amount, source_chain_id)` payouts of a round
+    fn send_payouts(&mut self, round_id: u64, payouts: Vec<(AccountOwner, Amount, Option<String>)>, memo: &str) {
+        if payouts.is_empty() {
+            return;
+        }
+        
+        let params = self.runtime.application_parameters();
+        if let Some(router_app_id) = params.router_app_id {
+            // Tickets were bought through Microbetreal - pay everything from its escrow in one call
+            let rewards = payouts.into_iter()
+                .map(|(owner, amount, source_chain_id)| native_fungible_abi::RewardPayout {
+                    recipient: owner,
+                    amount,
+                    source_chain_id,
+                })
+                .collect();
+            let _response: ExtendedResponse = self.runtime.call_application(
+                true,
+                router_app_id.with_abi::<ExtendedNativeFungibleTokenAbi>(),
+                &ExtendedOperation::SendRewards {
+                    rewards,
+                    round_id: Some(round_id),
+                    memo: Some(memo.to_string()),
+                    currency_app_id: None,
+                },
+            );
+        } else {
+            // Get lottery app ID from state (set via SetLotteryAppId operation)
+            let lottery_app_id = self.state.lottery_app_id.get()
+                .expect("Lottery app ID not set - run SetLotteryAppId first");
+
+            // Call lottery-app to send each payout
+            for (owner, amount, source_chain_id) in payouts {
+                let _response: LotteryAppResponse = self.runtime.call_application(
+                    true, // authenticated
+                    lottery_app_id,
+                    &LotteryAppOperation::SendPrize {
+                        recipient: owner,
+                        amount,
+                        source_chain_id,
+                    },
+                );
+            }
+        }
+    }
+}
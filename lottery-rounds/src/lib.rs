@@ -12,11 +12,20 @@ pub use lottery_abi::{
     LotteryRoundsParameters,
     LotteryRoundsMessage as Message,
     RoundStatus,
-    WinnerPool,
+    PrizeTier,
     LotteryRound,
     TicketPurchase,
     TicketPurchaseInfo,
     LotteryWinnerInfo,
+    TicketRange,
+    UserTickets,
+    LotteryRoundSummary,
+    RoundWinnerSummary,
+    // Draw verification
+    DrawProof,
+    DrawRecord,
+    PurchaseRecord,
+    verify_draw_proof,
     // Lottery App types (for cross-app calls)
     LotteryAppAbi,
     LotteryAppOperation,
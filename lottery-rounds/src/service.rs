@@ -8,15 +8,15 @@ mod state;
 use std::sync::Arc;
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    linera_base_types::{AccountOwner, Amount, CryptoHash, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
 use lottery_abi::{
-    LotteryRoundsAbi, LotteryRound, RoundStatus, WinnerPool, TicketPurchaseInfo, LotteryWinnerInfo,
-    LotteryRoundsParameters, LotteryRoundsOperation,
+    LotteryRoundsAbi, LotteryRound, RoundStatus, PrizeTier, TicketPurchaseInfo, LotteryWinnerInfo,
+    LotteryRoundsParameters, LotteryRoundsOperation, DrawProof, UserTickets, LotteryRoundSummary,
 };
-use self::state::{LotteryRoundsState, LotteryRound as StateLotteryRound, RoundStatus as StateRoundStatus, WinnerPool as StateWinnerPool};
+use self::state::{LotteryRoundsState, LotteryRound as StateLotteryRound, RoundStatus as StateRoundStatus};
 
 pub struct LotteryRoundsService {
     state: Arc<LotteryRoundsState>,
@@ -69,16 +69,7 @@ fn convert_round_status(status: StateRoundStatus) -> RoundStatus {
         StateRoundStatus::Active => RoundStatus::Active,
         StateRoundStatus::Closed => RoundStatus::Closed,
         StateRoundStatus::Complete => RoundStatus::Complete,
-    }
-}
-
-fn convert_winner_pool(pool: StateWinnerPool) -> WinnerPool {
-    match pool {
-        StateWinnerPool::Pool1 => WinnerPool::Pool1,
-        StateWinnerPool::Pool2 => WinnerPool::Pool2,
-        StateWinnerPool::Pool3 => WinnerPool::Pool3,
-        StateWinnerPool::Pool4 => WinnerPool::Pool4,
-        StateWinnerPool::Complete => WinnerPool::Complete,
+        StateRoundStatus::Refunded => RoundStatus::Refunded,
     }
 }
 
@@ -92,15 +83,13 @@ fn convert_round(round: StateLotteryRound) -> LotteryRound {
         total_tickets_sold: round.total_tickets_sold,
         next_ticket_number: round.next_ticket_number,
         prize_pool: round.prize_pool,
-        current_winner_pool: convert_winner_pool(round.current_winner_pool),
-        pool1_count: round.pool1_count,
-        pool2_count: round.pool2_count,
-        pool3_count: round.pool3_count,
-        pool4_count: round.pool4_count,
-        pool1_winners_drawn: round.pool1_winners_drawn,
-        pool2_winners_drawn: round.pool2_winners_drawn,
-        pool3_winners_drawn: round.pool3_winners_drawn,
-        pool4_winners_drawn: round.pool4_winners_drawn,
+        prize_tiers: round.prize_tiers,
+        current_tier: round.current_tier,
+        tier_winner_counts: round.tier_winner_counts,
+        tier_winners_drawn: round.tier_winners_drawn,
+        seed_commitment: round.seed_commitment,
+        entropy: round.entropy,
+        draw_seed: round.draw_seed,
     }
 }
 
@@ -144,16 +133,20 @@ impl QueryRoot {
             .collect()
     }
 
-    /// Get user tickets for a round
-    async fn user_tickets(&self, round_id: u64, owner: AccountOwner) -> Option<TicketPurchaseInfo> {
-        let purchase = self.state.get_user_tickets(round_id, owner.clone()).await.ok()??;
-        Some(TicketPurchaseInfo {
+    /// Get all tickets a user bought in a round: one range per purchase, and every ticket number
+    async fn user_tickets(&self, round_id: u64, owner: AccountOwner) -> Option<UserTickets> {
+        let owner_tickets = self.state.get_user_tickets(round_id, owner).await.ok()??;
+        let ticket_numbers = owner_tickets.ranges.iter()
+            .flat_map(|range| range.first_ticket..=range.last_ticket)
+            .collect();
+        Some(UserTickets {
+            round_id,
             owner,
-            first_ticket: purchase.first_ticket,
-            last_ticket: purchase.last_ticket,
-            total_tickets: purchase.total_tickets,
-            amount_paid: purchase.amount_paid,
-            source_chain_id: purchase.source_chain_id,
+            total_tickets: owner_tickets.total_tickets,
+            amount_paid: owner_tickets.amount_paid,
+            ranges: owner_tickets.ranges,
+            ticket_numbers,
+            source_chain_id: owner_tickets.source_chain_id,
         })
     }
 
@@ -172,6 +165,23 @@ impl QueryRoot {
             .collect()
     }
     
+    /// Get the draw transcript of a round: seed commitment, purchases, revealed secret and every draw
+    /// Check it offline with `lottery_abi::verify_draw_proof`
+    async fn draw_proof(&self, round_id: u64) -> Option<DrawProof> {
+        self.state.get_draw_proof(round_id).await.ok()?
+    }
+    
+    /// Get the permanent summary of a round: tickets sold, prize pool, winners and prizes
+    /// Summaries stay available after the round's tickets and draw transcript are cleaned up
+    async fn round_summary(&self, round_id: u64) -> Option<LotteryRoundSummary> {
+        self.state.get_round_summary(round_id).await.ok()?
+    }
+    
+    /// Get the summaries of all rounds that closed or were cleaned up
+    async fn round_summaries(&self) -> Vec<LotteryRoundSummary> {
+        self.state.get_round_summaries().await.unwrap_or_default()
+    }
+    
     /// Get the configured Native app ID
     async fn native_app_id(&self) -> String {
         let params = self.runtime.application_parameters();
@@ -191,27 +201,78 @@ struct MutationRoot {
 #[Object]
 impl MutationRoot {
     /// Create a new lottery round with the specified ticket price
-    async fn create_round(&self, ticket_price: String) -> String {
+    /// `seed_commitment` commits to the secret revealed at the first draw (it can also be set later with `commitSeed`)
+    /// `prize_tiers` splits the prize pool between winner tiers, drawn in order (defaults to 15/7/5/3% of tickets)
+    async fn create_round(&self, ticket_price: String, seed_commitment: Option<CryptoHash>, prize_tiers: Option<Vec<PrizeTier>>) -> String {
         self.runtime.schedule_operation(&LotteryRoundsOperation::CreateRound {
             ticket_price: ticket_price.parse::<Amount>().unwrap_or_default(),
+            seed_commitment,
+            prize_tiers: prize_tiers.unwrap_or_default(),
         });
         "CreateRound operation scheduled".to_string()
     }
     
+    /// Commit to the secret of a round's draw, required before the first ticket is sold
+    /// Rounds created automatically after a draw have no commitment until the operator commits one
+    async fn commit_seed(&self, round_id: u64, seed_commitment: CryptoHash) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::CommitSeed {
+            round_id,
+            seed_commitment,
+        });
+        format!("CommitSeed operation scheduled for round {}", round_id)
+    }
+    
+    /// Commit to the co-signer's secret of a round's draw, required before the first ticket is sold
+    /// when the app has a co-signer (signed by the co-signer)
+    async fn commit_cosigner_seed(&self, round_id: u64, seed_commitment: CryptoHash) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::CommitCosignerSeed {
+            round_id,
+            seed_commitment,
+        });
+        format!("CommitCosignerSeed operation scheduled for round {}", round_id)
+    }
+    
+    /// Reveal the co-signer's secret of a closed round, before its first draw (signed by the co-signer)
+    async fn reveal_cosigner_seed(&self, round_id: u64, secret: String) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::RevealCosignerSeed {
+            round_id,
+            secret,
+        });
+        format!("RevealCosignerSeed operation scheduled for round {}", round_id)
+    }
+    
     /// Close the active round (stops accepting purchases, prepares for drawing)
     async fn close_round(&self) -> String {
         self.runtime.schedule_operation(&LotteryRoundsOperation::CloseRound);
         "CloseRound operation scheduled".to_string()
     }
     
-    /// Generate one winner for a closed round using VRF
-    async fn generate_winner(&self, round_id: u64) -> String {
+    /// Generate one winner for a closed round
+    /// The first draw of a round must reveal the secret behind its seed commitment
+    async fn generate_winner(&self, round_id: u64, secret: Option<String>) -> String {
         self.runtime.schedule_operation(&LotteryRoundsOperation::GenerateWinner {
             round_id,
+            secret,
         });
         format!("GenerateWinner operation scheduled for round {}", round_id)
     }
     
+    /// Draw up to `max_count` winners of a closed round and pay them in one batch
+    async fn draw_winners(&self, round_id: u64, max_count: u64, secret: Option<String>) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::DrawWinners {
+            round_id,
+            max_count,
+            secret,
+        });
+        format!("DrawWinners operation scheduled for round {}", round_id)
+    }
+    
+    /// Pay back up to `max_count` ticket purchases of a round whose secret was not revealed in time
+    async fn refund_round(&self, round_id: u64, max_count: u64) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::RefundRound { round_id, max_count });
+        format!("RefundRound operation scheduled for round {}", round_id)
+    }
+    
     /// Set the Lottery App ID for cross-app calls
     async fn set_lottery_app_id(&self, lottery_app_id: String) -> String {
         self.runtime.schedule_operation(&LotteryRoundsOperation::SetLotteryAppId {
@@ -219,4 +280,10 @@ impl MutationRoot {
         });
         format!("SetLotteryAppId operation scheduled: {}", lottery_app_id)
     }
+    
+    /// Remove up to `max_entries` entries of rounds past the history limit, keeping their summaries
+    async fn cleanup_rounds(&self, max_entries: u64) -> String {
+        self.runtime.schedule_operation(&LotteryRoundsOperation::CleanupRounds { max_entries });
+        format!("CleanupRounds operation scheduled for up to {} entries", max_entries)
+    }
 }
@@ -2,42 +2,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext, ViewError};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId, CryptoHash};
 use serde::{Deserialize, Serialize};
 use async_graphql::SimpleObject;
 use num_bigint::BigUint;
 use num_traits::cast::ToPrimitive;
+use lottery_abi::{DrawProof, DrawRecord, LotteryRoundSummary, PrizeTier, PurchaseRecord, RoundWinnerSummary, TicketRange};
 
-/// Calculate prize amount for a specific winner pool
-/// Returns the portion of prize pool allocated to this pool
-fn calculate_prize_for_pool(prize_pool: Amount, pool: WinnerPool) -> Amount {
+/// Calculate prize amount for a prize tier
+/// Returns the portion of prize pool allocated to this tier
+fn calculate_prize_for_tier(prize_pool: Amount, tier: &PrizeTier) -> Amount {
     let prize_u128: u128 = u128::from(prize_pool);
-    
-    let percentage = match pool {
-        WinnerPool::Pool1 => 20, // 15% of tickets get 20% of prize
-        WinnerPool::Pool2 => 25, // 7% of tickets get 25% of prize
-        WinnerPool::Pool3 => 30, // 5% of tickets get 30% of prize
-        WinnerPool::Pool4 => 25, // 3% of tickets get 25% of prize
-        WinnerPool::Complete => 0,
-    };
-    
-    let pool_prize = (prize_u128 * percentage) / 100;
-    Amount::from_attos(pool_prize)
+    let tier_prize = (prize_u128 * u128::from(tier.prize_bps)) / u128::from(lottery_abi::BASIS_POINTS);
+    Amount::from_attos(tier_prize)
 }
 
-/// Calculate individual prize per winner in a pool
-/// Returns prize_for_pool / number_of_winners_in_pool
-fn calculate_prize_per_winner(prize_pool: Amount, pool: WinnerPool, winners_in_pool: u64) -> Amount {
-    if winners_in_pool == 0 {
+/// Calculate individual prize per winner in a tier
+/// Returns prize_for_tier / number_of_winners_in_tier
+fn calculate_prize_per_winner(prize_pool: Amount, tier: &PrizeTier, winners_in_tier: u64) -> Amount {
+    if winners_in_tier == 0 {
         return Amount::ZERO;
     }
     
-    let pool_prize = calculate_prize_for_pool(prize_pool, pool);
+    let pool_prize = calculate_prize_for_tier(prize_pool, tier);
     let pool_prize_u128: u128 = u128::from(pool_prize);
     
     // Using BigUint to prevent overflow
     let pool_prize_big = BigUint::from(pool_prize_u128);
-    let winners_big = BigUint::from(winners_in_pool);
+    let winners_big = BigUint::from(winners_in_tier);
     
     let prize_per_winner_big = pool_prize_big / winners_big;
     let prize_per_winner_u128 = prize_per_winner_big.to_u128().unwrap_or(u128::MAX);
@@ -45,24 +37,79 @@ fn calculate_prize_per_winner(prize_pool: Amount, pool: WinnerPool, winners_in_p
     Amount::from_attos(prize_per_winner_u128)
 }
 
+/// Summary of a round without its winners, which are stored one entry per winner in `summary_winners`
+fn round_summary(round: &LotteryRound) -> LotteryRoundSummary {
+    LotteryRoundSummary {
+        round_id: round.id,
+        created_at: round.created_at,
+        closed_at: round.closed_at,
+        ticket_price: round.ticket_price,
+        total_tickets_sold: round.total_tickets_sold,
+        prize_pool: round.prize_pool,
+        total_prizes: Amount::ZERO,
+        seed_commitment: round.seed_commitment,
+        draw_seed: round.draw_seed,
+        winners: Vec::new(),
+    }
+}
+
+/// Time until which the operator can reveal the secret of a closed round
+fn reveal_deadline(round: &LotteryRound) -> u64 {
+    round.closed_at.unwrap_or(round.created_at).saturating_add(lottery_abi::REVEAL_TIMEOUT_MICROS)
+}
+
 /// The application state for Lottery Rounds.
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct LotteryRoundsState {
     /// Counter for generating unique round IDs
     pub round_counter: RegisterView<u64>,
-    /// All lottery rounds
-    pub rounds: MapView<u64, LotteryRound>,
+    /// Lottery rounds from before prize tiers and draw seeds, which can only be refunded and cleaned up
+    pub rounds: MapView<u64, LegacyLotteryRound>,
     /// The currently active round (accepting ticket purchases)
     pub active_round: RegisterView<Option<u64>>,
-    /// Ticket purchases per round and user
+    /// Ticket purchases per round and user, no longer written (superseded by `purchases`)
     pub ticket_purchases: MapView<(u64, AccountOwner), TicketPurchase>,
-    /// Mapping from ticket number to owner
+    /// Mapping from ticket number to owner, no longer written (superseded by `purchase_records`)
     pub ticket_to_owner: MapView<(u64, u64), AccountOwner>,
     /// Winning tickets with prize info: (round_id, ticket_number) -> (owner, prize_amount, claimed, source_chain_id)
     pub winning_tickets: MapView<(u64, u64), (AccountOwner, Amount, bool, Option<String>)>,
     /// Lottery App ID for cross-app calls (stored as string, parsed at runtime)
     pub lottery_app_id: RegisterView<Option<ApplicationId<lottery_abi::LotteryAppAbi>>>,
+    /// Tickets of each owner in a round, across all their purchases
+    pub owner_tickets: MapView<(u64, AccountOwner), OwnerTickets>,
+    /// Shuffled ticket positions of each round's draw: (round_id, position) -> ticket_number
+    /// Only positions that moved are stored (see `lottery_abi::draw_position`)
+    pub draw_positions: MapView<(u64, u64), u64>,
+    /// Permanent summary of each closed round, kept after the round is cleaned up
+    pub round_summaries: MapView<u64, LotteryRoundSummary>,
+    /// Rounds past the history limit whose data is still being removed, oldest first
+    pub pending_cleanup: RegisterView<Vec<u64>>,
+    /// Number of purchases of each refunded round paid back so far
+    pub refunded_purchases: MapView<u64, u64>,
+    /// Purchases of each round in ticket order, as mixed into its entropy: (round_id, purchase index) -> purchase
+    /// Ticket ownership is stored as purchase ranges: binary search these records to find a ticket's purchase
+    pub purchase_records: MapView<(u64, u64), PurchaseRecord>,
+    /// Number of purchase records of each round
+    pub purchase_counts: MapView<u64, u64>,
+    /// Winner draws of each round: (round_id, draw index) -> draw
+    pub draw_records: MapView<(u64, u64), DrawRecord>,
+    /// Number of draw records of each round
+    pub draw_counts: MapView<u64, u64>,
+    /// Secret revealed at the first draw of each round
+    pub revealed_secrets: MapView<u64, String>,
+    /// Ledger of every ticket purchase: (round_id, first_ticket) -> purchase
+    pub purchases: MapView<(u64, u64), TicketPurchase>,
+    /// Winners of each round summary, kept with the summary: (round_id, draw index) -> winner
+    pub summary_winners: MapView<(u64, u64), RoundWinnerSummary>,
+    /// Number of summary winners of each round
+    pub summary_winner_counts: MapView<u64, u64>,
+    /// Co-signer's commitment to its secret of each round's draw
+    pub cosigner_commitments: MapView<u64, CryptoHash>,
+    /// Co-signer's secret of each round, revealed after the round closed
+    pub cosigner_secrets: MapView<u64, String>,
+    /// All lottery rounds with prize tiers and draw seeds
+    pub lottery_rounds: MapView<u64, LotteryRound>,
 }
 
 
@@ -77,6 +124,46 @@ pub struct LotteryRound {
     pub total_tickets_sold: u64,
     pub next_ticket_number: u64,
     pub prize_pool: Amount,
+    
+    // Prize tiers, drawn in order
+    pub prize_tiers: Vec<PrizeTier>,
+    pub current_tier: Option<u64>,     // None once all winners are drawn
+    pub tier_winner_counts: Vec<u64>,  // Calculated when round closes
+    pub tier_winners_drawn: Vec<u64>,
+    
+    // Draw randomness (commit-reveal, see lottery_abi::seed_commitment)
+    pub seed_commitment: Option<CryptoHash>,
+    pub entropy: CryptoHash,
+    pub draw_seed: Option<CryptoHash>,
+}
+
+impl LotteryRound {
+    /// Number of winners drawn so far, across all tiers
+    pub fn winners_drawn(&self) -> u64 {
+        self.tier_winners_drawn.iter().sum()
+    }
+    
+    /// First tier that still has winners to draw
+    fn next_tier(&self) -> Option<u64> {
+        self.tier_winner_counts.iter()
+            .zip(&self.tier_winners_drawn)
+            .position(|(count, drawn)| drawn < count)
+            .map(|tier| tier as u64)
+    }
+}
+
+/// A lottery round stored before prize tiers and draw seeds, with four fixed winner pools
+/// Its tickets are only in `ticket_to_owner`, so it cannot be drawn anymore: it is refunded instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyLotteryRound {
+    pub id: u64,
+    pub created_at: u64,
+    pub closed_at: Option<u64>,
+    pub status: RoundStatus,
+    pub ticket_price: Amount,
+    pub total_tickets_sold: u64,
+    pub next_ticket_number: u64,
+    pub prize_pool: Amount,
     pub current_winner_pool: WinnerPool,
     
     // Winner pool sizes (calculated when round closes)
@@ -92,16 +179,48 @@ pub struct LotteryRound {
     pub pool4_winners_drawn: u64,
 }
 
-/// Status of a lottery round
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
-pub enum RoundStatus {
-    Active,   // Accepting ticket purchases
-    Closed,   // Not accepting purchases, drawing winners
-    Complete, // All winners drawn
+impl LegacyLotteryRound {
+    /// The round as a round with the default prize tiers, which match its winner pools
+    pub fn to_round(&self) -> LotteryRound {
+        LotteryRound {
+            id: self.id,
+            created_at: self.created_at,
+            closed_at: self.closed_at,
+            status: self.status,
+            ticket_price: self.ticket_price,
+            total_tickets_sold: self.total_tickets_sold,
+            next_ticket_number: self.next_ticket_number,
+            prize_pool: self.prize_pool,
+            prize_tiers: lottery_abi::default_prize_tiers(),
+            current_tier: match self.current_winner_pool {
+                WinnerPool::Pool1 => Some(0),
+                WinnerPool::Pool2 => Some(1),
+                WinnerPool::Pool3 => Some(2),
+                WinnerPool::Pool4 => Some(3),
+                WinnerPool::Complete => None,
+            },
+            tier_winner_counts: vec![self.pool1_count, self.pool2_count, self.pool3_count, self.pool4_count],
+            tier_winners_drawn: vec![self.pool1_winners_drawn, self.pool2_winners_drawn, self.pool3_winners_drawn, self.pool4_winners_drawn],
+            seed_commitment: None,
+            entropy: lottery_abi::initial_entropy(self.id, self.created_at),
+            draw_seed: None,
+        }
+    }
+    
+    /// Prize pool left after the prizes already paid to drawn winners, shared by its tickets on refund
+    fn remaining_prize_pool(&self) -> Amount {
+        let round = self.to_round();
+        round.prize_tiers.iter()
+            .zip(round.tier_winner_counts.iter().zip(&round.tier_winners_drawn))
+            .fold(round.prize_pool, |remaining, (tier, (count, drawn))| {
+                let paid = u128::from(calculate_prize_per_winner(round.prize_pool, tier, *count)).saturating_mul(u128::from(*drawn));
+                remaining.saturating_sub(Amount::from_attos(paid))
+            })
+    }
 }
 
-/// Winner pool identifier
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+/// Winner pool of a legacy round
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum WinnerPool {
     Pool1,    // 15% of tickets, 20% of prize
     Pool2,    // 7% of tickets, 25% of prize
@@ -110,6 +229,15 @@ pub enum WinnerPool {
     Complete, // All winners drawn
 }
 
+/// Status of a lottery round
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum RoundStatus {
+    Active,   // Accepting ticket purchases
+    Closed,   // Not accepting purchases, drawing winners
+    Complete, // All winners drawn
+    Refunded, // Secret not revealed in time, tickets are paid back
+}
+
 /// A user's ticket purchase
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct TicketPurchase {
@@ -121,55 +249,205 @@ pub struct TicketPurchase {
     pub source_chain_id: Option<String>,
 }
 
+/// All tickets an owner bought in a round
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct OwnerTickets {
+    pub owner: AccountOwner,
+    pub total_tickets: u64,
+    pub amount_paid: Amount,
+    pub ranges: Vec<TicketRange>,
+    pub source_chain_id: Option<String>,
+}
+
 /// Maximum number of rounds to keep in history
 const MAX_HISTORY_ROUNDS: u64 = 5;
 
+/// Maximum number of entries removed when cleanup runs as part of another operation
+const CLEANUP_BATCH_SIZE: u64 = 100;
+
 #[allow(dead_code)]
 
 impl LotteryRoundsState {
-    /// Cleanup old round data (tickets, winners, round itself)
-    async fn cleanup_old_round(&mut self, round_id: u64) -> Result<(), String> {
-        eprintln!("Cleaning up old round: {}", round_id);
+    /// Remove up to `max_entries` entries of the rounds waiting for cleanup, oldest round first
+    /// Rounds still drawing winners or refunding tickets are skipped and stay queued
+    /// Returns the number of entries removed
+    pub async fn cleanup_rounds(&mut self, max_entries: u64) -> Result<u64, String> {
+        let pending = self.pending_cleanup.get().clone();
+        let mut remaining = Vec::new();
+        let mut budget = max_entries;
         
-        // Get the round to find all tickets
-        if let Ok(Some(round)) = self.rounds.get(&round_id).await {
-            let total_tickets = round.total_tickets_sold;
-            
-            // Remove all ticket_to_owner entries for this round
-            for ticket_num in 1..=total_tickets {
-                let _ = self.ticket_to_owner.remove(&(round_id, ticket_num));
+        for round_id in pending {
+            if budget == 0 || !self.cleanup_round_batch(round_id, &mut budget).await? {
+                remaining.push(round_id);
             }
-            
-            // Remove all winning_tickets entries for this round
-            // We need to iterate through potential winning tickets
-            for ticket_num in 1..=total_tickets {
-                let _ = self.winning_tickets.remove(&(round_id, ticket_num));
+        }
+        
+        self.pending_cleanup.set(remaining);
+        Ok(max_entries - budget)
+    }
+    
+    /// Remove old round data (winners, draw positions, purchases, round itself) within `budget` entries
+    /// The round's summary is kept. Returns whether the round is fully removed, which a round
+    /// still drawing winners or refunding tickets never is
+    async fn cleanup_round_batch(&mut self, round_id: u64, budget: &mut u64) -> Result<bool, String> {
+        let Some(round) = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))? else {
+            return match self.rounds.get(&round_id).await
+                .map_err(|e: ViewError| format!("Failed to get legacy round: {:?}", e))? {
+                Some(legacy) => self.cleanup_legacy_round_batch(legacy, budget).await,
+                None => Ok(true),
+            };
+        };
+        
+        // Winners are still being drawn
+        if round.status == RoundStatus::Closed {
+            return Ok(false);
+        }
+        // Tickets are still being refunded
+        if round.status == RoundStatus::Refunded && !self.refunds_complete(round_id).await? {
+            return Ok(false);
+        }
+        
+        // Rounds that never closed have no summary yet
+        if self.round_summaries.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round summary: {:?}", e))?
+            .is_none() {
+            self.round_summaries.insert(&round_id, round_summary(&round))
+                .map_err(|e: ViewError| format!("Failed to insert round summary: {:?}", e))?;
+        }
+        
+        // Remove winners and draw positions, found from the draw records, last draw first
+        // Every stored draw position is the position of one of the draws
+        let mut draws = self.get_draw_count(round_id).await?;
+        while *budget > 0 && draws > 0 {
+            draws -= 1;
+            if let Some(draw) = self.draw_records.get(&(round_id, draws)).await
+                .map_err(|e: ViewError| format!("Failed to get draw record: {:?}", e))? {
+                self.winning_tickets.remove(&(round_id, draw.ticket_number))
+                    .map_err(|e: ViewError| format!("Failed to remove winning ticket: {:?}", e))?;
+                self.draw_positions.remove(&(round_id, draw.position))
+                    .map_err(|e: ViewError| format!("Failed to remove draw position: {:?}", e))?;
             }
+            self.draw_records.remove(&(round_id, draws))
+                .map_err(|e: ViewError| format!("Failed to remove draw record: {:?}", e))?;
+            *budget -= 1;
+        }
+        self.draw_counts.insert(&round_id, draws)
+            .map_err(|e: ViewError| format!("Failed to update draw count: {:?}", e))?;
+        if draws > 0 {
+            return Ok(false);
         }
         
-        // Remove ticket purchases for this round - we need to collect keys first
-        // Since we can't easily iterate MapView with compound keys, we'll just remove the round
-        // Note: ticket_purchases keyed by (round_id, AccountOwner) - can't easily iterate
+        // Remove ticket purchases, one entry per purchase, last purchase first
+        let mut purchases = self.get_purchase_count(round_id).await?;
+        while *budget > 0 && purchases > 0 {
+            purchases -= 1;
+            if let Some(record) = self.purchase_records.get(&(round_id, purchases)).await
+                .map_err(|e: ViewError| format!("Failed to get purchase record: {:?}", e))? {
+                self.owner_tickets.remove(&(round_id, record.owner))
+                    .map_err(|e: ViewError| format!("Failed to remove owner tickets: {:?}", e))?;
+                self.purchases.remove(&(round_id, record.first_ticket))
+                    .map_err(|e: ViewError| format!("Failed to remove ticket purchase: {:?}", e))?;
+            }
+            self.purchase_records.remove(&(round_id, purchases))
+                .map_err(|e: ViewError| format!("Failed to remove purchase record: {:?}", e))?;
+            *budget -= 1;
+        }
+        self.purchase_counts.insert(&round_id, purchases)
+            .map_err(|e: ViewError| format!("Failed to update purchase count: {:?}", e))?;
+        if purchases > 0 {
+            return Ok(false);
+        }
+        
+        self.purchase_counts.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove purchase count: {:?}", e))?;
+        self.draw_counts.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove draw count: {:?}", e))?;
+        self.revealed_secrets.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove revealed secret: {:?}", e))?;
+        self.cosigner_commitments.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove co-signer commitment: {:?}", e))?;
+        self.cosigner_secrets.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove co-signer secret: {:?}", e))?;
+        self.refunded_purchases.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove refund progress: {:?}", e))?;
         
         // Remove the round itself
-        self.rounds.remove(&round_id)
-            .map_err(|e| format!("Failed to remove round {}: {:?}", round_id, e))?;
+        self.lottery_rounds.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove round {}: {:?}", round_id, e))?;
         
         eprintln!("Cleaned up round: {}", round_id);
-        Ok(())
+        Ok(true)
+    }
+    
+    /// Remove the tickets, winners and purchases of a legacy round within `budget` entries, last ticket first
+    /// Its remaining ticket count is the cleanup cursor. The round's summary is kept.
+    /// Returns whether the round is fully removed, which a round not refunded yet never is
+    async fn cleanup_legacy_round_batch(&mut self, mut legacy: LegacyLotteryRound, budget: &mut u64) -> Result<bool, String> {
+        let round_id = legacy.id;
+        match legacy.status {
+            RoundStatus::Complete => {}
+            RoundStatus::Refunded if self.refunds_complete(round_id).await? => {}
+            // Tickets still hold their stakes until the round is refunded
+            _ => return Ok(false),
+        }
+        
+        if self.round_summaries.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round summary: {:?}", e))?
+            .is_none() {
+            self.round_summaries.insert(&round_id, round_summary(&legacy.to_round()))
+                .map_err(|e: ViewError| format!("Failed to insert round summary: {:?}", e))?;
+        }
+        
+        while *budget > 0 && legacy.total_tickets_sold > 0 {
+            let ticket_number = legacy.total_tickets_sold;
+            if let Some(owner) = self.ticket_to_owner.get(&(round_id, ticket_number)).await
+                .map_err(|e: ViewError| format!("Failed to get ticket owner: {:?}", e))? {
+                self.ticket_purchases.remove(&(round_id, owner))
+                    .map_err(|e: ViewError| format!("Failed to remove ticket purchase: {:?}", e))?;
+            }
+            self.ticket_to_owner.remove(&(round_id, ticket_number))
+                .map_err(|e: ViewError| format!("Failed to remove ticket owner: {:?}", e))?;
+            self.winning_tickets.remove(&(round_id, ticket_number))
+                .map_err(|e: ViewError| format!("Failed to remove winning ticket: {:?}", e))?;
+            legacy.total_tickets_sold -= 1;
+            *budget -= 1;
+        }
+        if legacy.total_tickets_sold > 0 {
+            self.rounds.insert(&round_id, legacy)
+                .map_err(|e: ViewError| format!("Failed to update legacy round: {:?}", e))?;
+            return Ok(false);
+        }
+        
+        self.refunded_purchases.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove refund progress: {:?}", e))?;
+        self.rounds.remove(&round_id)
+            .map_err(|e: ViewError| format!("Failed to remove legacy round {}: {:?}", round_id, e))?;
+        eprintln!("Cleaned up legacy round: {}", round_id);
+        Ok(true)
     }
     
     /// Creates a new lottery round with specified ticket price
-    pub async fn create_lottery_round(&mut self, ticket_price: Amount, timestamp: u64) -> Result<u64, String> {
+    /// The round sells no ticket before the operator commits to the secret of its draw
+    /// Without `prize_tiers`, the round uses the default tiers
+    pub async fn create_lottery_round(&mut self, ticket_price: Amount, timestamp: u64, seed_commitment: Option<CryptoHash>, prize_tiers: Vec<PrizeTier>) -> Result<u64, String> {
+        let prize_tiers = if prize_tiers.is_empty() {
+            lottery_abi::default_prize_tiers()
+        } else {
+            prize_tiers
+        };
+        lottery_abi::validate_prize_tiers(&prize_tiers)?;
+        
         let round_id = *self.round_counter.get() + 1;
         self.round_counter.set(round_id);
         
-        // Cleanup oldest rounds if we exceed MAX_HISTORY_ROUNDS
+        // Cleanup oldest rounds if we exceed MAX_HISTORY_ROUNDS, a batch at a time
         if round_id > MAX_HISTORY_ROUNDS {
             let oldest_round_id = round_id - MAX_HISTORY_ROUNDS;
             eprintln!("Round {} exceeds history limit, cleaning up round {}", round_id, oldest_round_id);
-            let _ = self.cleanup_old_round(oldest_round_id).await;
+            self.pending_cleanup.get_mut().push(oldest_round_id);
         }
+        self.cleanup_rounds(CLEANUP_BATCH_SIZE).await?;
         
         let round = LotteryRound {
             id: round_id,
@@ -180,38 +458,326 @@ impl LotteryRoundsState {
             total_tickets_sold: 0,
             next_ticket_number: 1, // Tickets start from 1
             prize_pool: Amount::ZERO,
-            current_winner_pool: WinnerPool::Pool1,
-            pool1_count: 0,
-            pool2_count: 0,
-            pool3_count: 0,
-            pool4_count: 0,
-            pool1_winners_drawn: 0,
-            pool2_winners_drawn: 0,
-            pool3_winners_drawn: 0,
-            pool4_winners_drawn: 0,
+            current_tier: Some(0),
+            tier_winner_counts: Vec::new(),
+            tier_winners_drawn: vec![0; prize_tiers.len()],
+            prize_tiers,
+            seed_commitment,
+            entropy: lottery_abi::initial_entropy(round_id, timestamp),
+            draw_seed: None,
         };
         
-        self.rounds.insert(&round_id, round)
+        self.lottery_rounds.insert(&round_id, round)
             .map_err(|e: ViewError| format!("Failed to insert round: {:?}", e))?;
         self.active_round.set(Some(round_id));
         
         Ok(round_id)
     }
     
+    /// Number of purchase records of a round
+    async fn get_purchase_count(&self, round_id: u64) -> Result<u64, String> {
+        Ok(self.purchase_counts.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get purchase count: {:?}", e))?
+            .unwrap_or_default())
+    }
+    
+    /// Number of draw records of a round
+    async fn get_draw_count(&self, round_id: u64) -> Result<u64, String> {
+        Ok(self.draw_counts.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get draw count: {:?}", e))?
+            .unwrap_or_default())
+    }
+    
+    /// Assemble the draw transcript of a round from its purchase and draw records
+    pub async fn get_draw_proof(&self, round_id: u64) -> Result<Option<DrawProof>, String> {
+        let Some(round) = self.get_round(round_id).await? else {
+            return Ok(None);
+        };
+        
+        let purchase_count = self.get_purchase_count(round_id).await?;
+        let mut purchases = Vec::with_capacity(purchase_count as usize);
+        for index in 0..purchase_count {
+            if let Some(record) = self.purchase_records.get(&(round_id, index)).await
+                .map_err(|e: ViewError| format!("Failed to get purchase record: {:?}", e))? {
+                purchases.push(record);
+            }
+        }
+        
+        let draw_count = self.get_draw_count(round_id).await?;
+        let mut draws = Vec::with_capacity(draw_count as usize);
+        for index in 0..draw_count {
+            if let Some(draw) = self.draw_records.get(&(round_id, index)).await
+                .map_err(|e: ViewError| format!("Failed to get draw record: {:?}", e))? {
+                draws.push(draw);
+            }
+        }
+        
+        let secret = self.revealed_secrets.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get revealed secret: {:?}", e))?;
+        let cosigner_commitment = self.cosigner_commitments.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get co-signer commitment: {:?}", e))?;
+        let cosigner_secret = self.cosigner_secrets.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get co-signer secret: {:?}", e))?;
+        
+        Ok(Some(DrawProof {
+            round_id,
+            created_at: round.created_at,
+            seed_commitment: round.seed_commitment,
+            purchases,
+            entropy: round.entropy,
+            secret,
+            cosigner_commitment,
+            cosigner_secret,
+            draw_seed: round.draw_seed,
+            total_tickets: round.total_tickets_sold,
+            draws,
+        }))
+    }
+    
+    /// Commit to the secret of a round's draw, if the round has no commitment and no ticket sold yet
+    pub async fn commit_seed(&mut self, round_id: u64, seed_commitment: CryptoHash) -> Result<(), String> {
+        let mut round = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
+            .ok_or("Round not found")?;
+        
+        if round.status != RoundStatus::Active {
+            return Err("Seed can only be committed while the round is active".to_string());
+        }
+        if round.seed_commitment.is_some() {
+            return Err("Round already has a seed commitment".to_string());
+        }
+        if round.total_tickets_sold > 0 {
+            return Err("Seed must be committed before the first ticket is sold".to_string());
+        }
+        
+        round.seed_commitment = Some(seed_commitment);
+        self.lottery_rounds.insert(&round_id, round)
+            .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))
+    }
+    
+    /// Commit to the co-signer's secret of a round's draw, if the round has no such commitment and no ticket sold yet
+    pub async fn commit_cosigner_seed(&mut self, round_id: u64, seed_commitment: CryptoHash) -> Result<(), String> {
+        let round = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
+            .ok_or("Round not found")?;
+        
+        if round.status != RoundStatus::Active {
+            return Err("Seed can only be committed while the round is active".to_string());
+        }
+        if self.cosigner_commitments.contains_key(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get co-signer commitment: {:?}", e))? {
+            return Err("Round already has a co-signer commitment".to_string());
+        }
+        if round.total_tickets_sold > 0 {
+            return Err("Seed must be committed before the first ticket is sold".to_string());
+        }
+        
+        self.cosigner_commitments.insert(&round_id, seed_commitment)
+            .map_err(|e: ViewError| format!("Failed to store co-signer commitment: {:?}", e))
+    }
+    
+    /// Reveal the co-signer's secret of a closed round, which must match its commitment
+    /// Like the operator's secret, it can only be revealed until `REVEAL_TIMEOUT_MICROS` after the round closed
+    pub async fn reveal_cosigner_seed(&mut self, round_id: u64, secret: String, now: u64) -> Result<(), String> {
+        let round = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
+            .ok_or("Round not found")?;
+        
+        if round.status != RoundStatus::Closed || round.draw_seed.is_some() {
+            return Err("Secret can only be revealed after the round closed and before its first draw".to_string());
+        }
+        if now > reveal_deadline(&round) {
+            return Err("The reveal deadline has passed, the round can only be refunded".to_string());
+        }
+        let seed_commitment = self.cosigner_commitments.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get co-signer commitment: {:?}", e))?
+            .ok_or("Round has no co-signer commitment")?;
+        if lottery_abi::cosigner_commitment(round_id, &secret) != seed_commitment {
+            return Err("Secret does not match the co-signer commitment".to_string());
+        }
+        
+        self.cosigner_secrets.insert(&round_id, secret)
+            .map_err(|e: ViewError| format!("Failed to store co-signer secret: {:?}", e))
+    }
+    
+    /// Get the draw seed of a closed round, revealing the operator's secret if it is not known yet
+    /// The secret can only be revealed until `REVEAL_TIMEOUT_MICROS` after the round closed,
+    /// and, for a co-signed round, once the co-signer's secret is revealed
+    pub async fn reveal_draw_seed(&mut self, round_id: u64, secret: Option<String>, now: u64) -> Result<CryptoHash, String> {
+        let mut round = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
+            .ok_or("Round not found")?;
+        
+        if let Some(draw_seed) = round.draw_seed {
+            return Ok(draw_seed);
+        }
+        if round.status != RoundStatus::Closed {
+            return Err("Round is not closed".to_string());
+        }
+        if now > reveal_deadline(&round) {
+            return Err("The reveal deadline has passed, the round can only be refunded".to_string());
+        }
+        
+        let secret = secret.ok_or("The first draw of a round must reveal its secret")?;
+        let seed_commitment = round.seed_commitment.ok_or("Round has no seed commitment")?;
+        if lottery_abi::seed_commitment(round_id, &secret) != seed_commitment {
+            return Err("Secret does not match the seed commitment".to_string());
+        }
+        
+        let cosigner_secret = if self.cosigner_commitments.contains_key(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get co-signer commitment: {:?}", e))? {
+            Some(self.cosigner_secrets.get(&round_id).await
+                .map_err(|e: ViewError| format!("Failed to get co-signer secret: {:?}", e))?
+                .ok_or("The co-signer has not revealed its secret yet")?)
+        } else {
+            None
+        };
+        
+        let draw_seed = lottery_abi::draw_seed(round_id, &secret, round.entropy, cosigner_secret.as_deref());
+        round.draw_seed = Some(draw_seed);
+        self.lottery_rounds.insert(&round_id, round)
+            .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))?;
+        self.revealed_secrets.insert(&round_id, secret)
+            .map_err(|e: ViewError| format!("Failed to store revealed secret: {:?}", e))?;
+        Ok(draw_seed)
+    }
+    
+    /// Pay back up to `max_count` purchases of a closed round whose secret was not revealed before the deadline
+    /// Returns the refunds as (owner, amount_paid, source_chain_id)
+    /// A legacy round is refunded instead at any time, see `refund_legacy_round`
+    pub async fn refund_round(&mut self, round_id: u64, max_count: u64, now: u64) -> Result<Vec<(AccountOwner, Amount, Option<String>)>, String> {
+        let Some(mut round) = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))? else {
+            let legacy = self.rounds.get(&round_id).await
+                .map_err(|e: ViewError| format!("Failed to get legacy round: {:?}", e))?
+                .ok_or("Round not found")?;
+            return self.refund_legacy_round(legacy, max_count).await;
+        };
+        
+        match round.status {
+            RoundStatus::Closed if round.draw_seed.is_none() => {
+                if now <= reveal_deadline(&round) {
+                    return Err("The operator can still reveal the secret of this round".to_string());
+                }
+                round.status = RoundStatus::Refunded;
+                self.lottery_rounds.insert(&round_id, round)
+                    .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))?;
+            }
+            RoundStatus::Refunded => {}
+            _ => return Err("Only a closed round whose secret was never revealed can be refunded".to_string()),
+        }
+        
+        let purchase_count = self.get_purchase_count(round_id).await?;
+        let mut refunded = self.refunded_purchases.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get refund progress: {:?}", e))?
+            .unwrap_or_default();
+        let mut refunds = Vec::new();
+        while refunded < purchase_count && (refunds.len() as u64) < max_count {
+            if let Some(record) = self.purchase_records.get(&(round_id, refunded)).await
+                .map_err(|e: ViewError| format!("Failed to get purchase record: {:?}", e))? {
+                if let Some(purchase) = self.purchases.get(&(round_id, record.first_ticket)).await
+                    .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))? {
+                    refunds.push((purchase.owner, purchase.amount_paid, purchase.source_chain_id));
+                }
+            }
+            refunded += 1;
+        }
+        self.refunded_purchases.insert(&round_id, refunded)
+            .map_err(|e: ViewError| format!("Failed to update refund progress: {:?}", e))?;
+        Ok(refunds)
+    }
+    
+    /// Pay back up to `max_count` tickets of a legacy round that was not completed
+    /// Each ticket gets an equal share of what is left of the prize pool after the prizes already paid,
+    /// and consecutive tickets of the same owner are paid together. Progress counts tickets, not purchases
+    /// Returns the refunds as (owner, amount, source_chain_id)
+    async fn refund_legacy_round(&mut self, mut legacy: LegacyLotteryRound, max_count: u64) -> Result<Vec<(AccountOwner, Amount, Option<String>)>, String> {
+        let round_id = legacy.id;
+        match legacy.status {
+            RoundStatus::Active | RoundStatus::Closed => {
+                // The round can no longer be drawn, so it stops selling tickets for good
+                legacy.status = RoundStatus::Refunded;
+                self.rounds.insert(&round_id, legacy.clone())
+                    .map_err(|e: ViewError| format!("Failed to update legacy round: {:?}", e))?;
+                if *self.active_round.get() == Some(round_id) {
+                    self.active_round.set(None);
+                }
+            }
+            RoundStatus::Refunded => {}
+            RoundStatus::Complete => return Err("A completed round cannot be refunded".to_string()),
+        }
+        
+        let total_tickets = legacy.total_tickets_sold;
+        let share = u128::from(legacy.remaining_prize_pool()).checked_div(u128::from(total_tickets)).unwrap_or(0);
+        let mut refunded = self.refunded_purchases.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get refund progress: {:?}", e))?
+            .unwrap_or_default();
+        let end = total_tickets.min(refunded.saturating_add(max_count));
+        let mut refunds: Vec<(AccountOwner, Amount, Option<String>)> = Vec::new();
+        let mut previous_owner = None;
+        while refunded < end {
+            refunded += 1;
+            let Some(owner) = self.ticket_to_owner.get(&(round_id, refunded)).await
+                .map_err(|e: ViewError| format!("Failed to get ticket owner: {:?}", e))? else {
+                previous_owner = None;
+                continue;
+            };
+            let amount = Amount::from_attos(share);
+            if previous_owner == Some(owner) {
+                if let Some((_, total, _)) = refunds.last_mut() {
+                    *total = total.saturating_add(amount);
+                }
+            } else {
+                let source_chain_id = self.ticket_purchases.get(&(round_id, owner)).await
+                    .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))?
+                    .and_then(|purchase| purchase.source_chain_id);
+                refunds.push((owner, amount, source_chain_id));
+            }
+            previous_owner = Some(owner);
+        }
+        self.refunded_purchases.insert(&round_id, refunded)
+            .map_err(|e: ViewError| format!("Failed to update refund progress: {:?}", e))?;
+        Ok(refunds)
+    }
+    
+    /// Whether every purchase of a refunded round (every ticket of a legacy round) was paid back
+    async fn refunds_complete(&self, round_id: u64) -> Result<bool, String> {
+        if let Some(legacy) = self.rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get legacy round: {:?}", e))? {
+            let refunded = self.refunded_purchases.get(&round_id).await
+                .map_err(|e: ViewError| format!("Failed to get refund progress: {:?}", e))?
+                .unwrap_or_default();
+            return Ok(refunded >= legacy.total_tickets_sold);
+        }
+        let purchases = self.get_purchase_count(round_id).await?;
+        let refunded = self.refunded_purchases.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get refund progress: {:?}", e))?
+            .unwrap_or_default();
+        Ok(refunded >= purchases)
+    }
+    
     /// Purchase tickets in the active round
     /// Called by lottery-app via cross-app call
-    pub async fn purchase_tickets(&mut self, owner: AccountOwner, amount: Amount, ticket_price: Amount, source_chain_id: Option<String>) -> Result<TicketPurchase, String> {
+    /// When the app has a co-signer (`cosigned`), tickets are only sold once it committed to its secret
+    pub async fn purchase_tickets(&mut self, owner: AccountOwner, amount: Amount, ticket_price: Amount, source_chain_id: Option<String>, cosigned: bool) -> Result<TicketPurchase, String> {
         let round_id_opt = self.active_round.get();
         
         if let Some(round_id) = *round_id_opt {
-            let mut round = self.rounds.get(&round_id).await
+            let mut round = self.lottery_rounds.get(&round_id).await
                 .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
-                .ok_or("Active round not found")?
+                .ok_or("Active round not found - a round from before prize tiers can only be refunded")?
                 .clone();
             
             if round.status != RoundStatus::Active {
                 return Err("No active round accepting ticket purchases".to_string());
             }
+            if round.seed_commitment.is_none() {
+                return Err("Tickets go on sale once the draw seed is committed".to_string());
+            }
+            if cosigned && !self.cosigner_commitments.contains_key(&round_id).await
+                .map_err(|e: ViewError| format!("Failed to get co-signer commitment: {:?}", e))? {
+                return Err("Tickets go on sale once the co-signer commits to its secret".to_string());
+            }
             
             // Use the round's ticket price, not the parameter (for backwards compatibility)
             let actual_ticket_price = round.ticket_price;
@@ -249,24 +815,44 @@ impl LotteryRoundsState {
                 source_chain_id: source_chain_id.clone(),
             };
             
-            // Store ticket purchase
-            self.ticket_purchases.insert(&(round_id, owner.clone()), purchase.clone())
+            // Store ticket purchase in the ledger and add it to the owner's tickets
+            self.purchases.insert(&(round_id, first_ticket), purchase.clone())
                 .map_err(|e: ViewError| format!("Failed to record purchase: {:?}", e))?;
-            
-            // Map each ticket to owner
-            for ticket_num in first_ticket..=last_ticket {
-                self.ticket_to_owner.insert(&(round_id, ticket_num), owner.clone())
-                    .map_err(|e: ViewError| format!("Failed to map ticket to owner: {:?}", e))?;
-            }
+            let mut owner_tickets = self.owner_tickets.get(&(round_id, owner)).await
+                .map_err(|e: ViewError| format!("Failed to get owner tickets: {:?}", e))?
+                .unwrap_or(OwnerTickets {
+                    owner,
+                    total_tickets: 0,
+                    amount_paid: Amount::ZERO,
+                    ranges: Vec::new(),
+                    source_chain_id: None,
+                });
+            owner_tickets.total_tickets += ticket_count_u64;
+            owner_tickets.amount_paid = owner_tickets.amount_paid.saturating_add(amount);
+            owner_tickets.ranges.push(TicketRange { first_ticket, last_ticket });
+            owner_tickets.source_chain_id = source_chain_id.clone();
+            self.owner_tickets.insert(&(round_id, owner), owner_tickets)
+                .map_err(|e: ViewError| format!("Failed to record owner tickets: {:?}", e))?;
             
             // Update round
+            round.entropy = lottery_abi::mix_purchase_entropy(round.entropy, owner, amount, first_ticket, last_ticket);
             round.next_ticket_number = last_ticket + 1;
             round.total_tickets_sold += ticket_count_u64;
             round.prize_pool = round.prize_pool.saturating_add(amount);
-            
-            self.rounds.insert(&round_id, round)
+            self.lottery_rounds.insert(&round_id, round)
                 .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))?;
             
+            // Record the purchase in the draw transcript, in the order it was mixed into the entropy
+            // Tickets are sold in order, so the records stay sorted by ticket range
+            let purchase_count = self.get_purchase_count(round_id).await?;
+            self.purchase_records.insert(&(round_id, purchase_count), PurchaseRecord { owner, amount, first_ticket, last_ticket })
+                .map_err(|e: ViewError| format!("Failed to record purchase: {:?}", e))?;
+            self.purchase_counts.insert(&round_id, purchase_count + 1)
+                .map_err(|e: ViewError| format!("Failed to update purchase count: {:?}", e))?;
+            
+            // Keep removing old rounds a batch at a time
+            self.cleanup_rounds(CLEANUP_BATCH_SIZE).await?;
+            
             Ok(purchase)
         } else {
             Err("No active round".to_string())
@@ -278,46 +864,36 @@ impl LotteryRoundsState {
         let round_id_opt = self.active_round.get();
         
         if let Some(round_id) = *round_id_opt {
-            let mut round = self.rounds.get(&round_id).await
+            let mut round = self.lottery_rounds.get(&round_id).await
                 .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
-                .ok_or("Active round not found")?
+                .ok_or("Active round not found - a round from before prize tiers can only be refunded")?
                 .clone();
             
             if round.status != RoundStatus::Active {
                 return Err("Round is not active".to_string());
             }
             
-            if round.total_tickets_sold < 4 {
-                return Err("Cannot close round with fewer than 4 tickets sold".to_string());
+            // Every tier must get its winners, or its share of the prize pool is never paid
+            let min_tickets = lottery_abi::required_winners(&round.prize_tiers);
+            if round.total_tickets_sold < min_tickets {
+                return Err(format!("Cannot close round with fewer than {} tickets sold", min_tickets));
             }
             
-            // Calculate winner pool sizes
-            // 30% of tickets are winners: 15% + 7% + 5% + 3% = 30%
-            let total_tickets = round.total_tickets_sold;
-            round.pool1_count = (total_tickets * 15) / 100; // 15% of tickets
-            round.pool2_count = (total_tickets * 7) / 100;  // 7% of tickets
-            round.pool3_count = (total_tickets * 5) / 100;  // 5% of tickets
-            round.pool4_count = (total_tickets * 3) / 100;  // 3% of tickets
-            
-            // Ensure at least 1 winner in each pool if there are tickets
-            if round.pool1_count == 0 && total_tickets > 0 {
-                round.pool1_count = 1;
-            }
-            if round.pool2_count == 0 && total_tickets > 1 {
-                round.pool2_count = 1;
-            }
-            if round.pool3_count == 0 && total_tickets > 2 {
-                round.pool3_count = 1;
-            }
-            if round.pool4_count == 0 && total_tickets > 3 {
-                round.pool4_count = 1;
+            if round.seed_commitment.is_none() {
+                return Err("Cannot close round before the draw seed is committed".to_string());
             }
             
+            // Calculate the number of winners of each tier (its fixed count, or at least 1)
+            round.tier_winner_counts = lottery_abi::tier_winner_counts(&round.prize_tiers, round.total_tickets_sold);
+            round.tier_winners_drawn = vec![0; round.prize_tiers.len()];
+            
             round.status = RoundStatus::Closed;
             round.closed_at = Some(timestamp);
-            round.current_winner_pool = WinnerPool::Pool1;
+            round.current_tier = round.next_tier();
             
-            self.rounds.insert(&round_id, round)
+            self.round_summaries.insert(&round_id, round_summary(&round))
+                .map_err(|e: ViewError| format!("Failed to insert round summary: {:?}", e))?;
+            self.lottery_rounds.insert(&round_id, round)
                 .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))?;
             self.active_round.set(None);
             
@@ -327,10 +903,10 @@ impl LotteryRoundsState {
         }
     }
     
-    /// Generate one winner using VRF and prepare for prize distribution
+    /// Generate the next winner from the round's draw seed and prepare for prize distribution
     /// Returns: (round_id, ticket_number, owner, prize_amount, new_round_created, source_chain_id)
-    pub async fn generate_winner(&mut self, vrf_value: u64, round_id: u64, current_timestamp: u64, default_ticket_price: Amount) -> Result<(u64, u64, AccountOwner, Amount, bool, Option<String>), String> {
-        let mut round = self.rounds.get(&round_id).await
+    pub async fn generate_winner(&mut self, draw_seed: CryptoHash, round_id: u64, current_timestamp: u64, default_ticket_price: Amount) -> Result<(u64, u64, AccountOwner, Amount, bool, Option<String>), String> {
+        let mut round = self.lottery_rounds.get(&round_id).await
             .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))?
             .ok_or("Round not found")?
             .clone();
@@ -339,114 +915,136 @@ impl LotteryRoundsState {
             return Err("Round is not closed".to_string());
         }
         
-        // Determine current pool and check if complete
-        let (pool, winners_count, winners_drawn) = match round.current_winner_pool {
-            WinnerPool::Pool1 => (WinnerPool::Pool1, round.pool1_count, round.pool1_winners_drawn),
-            WinnerPool::Pool2 => (WinnerPool::Pool2, round.pool2_count, round.pool2_winners_drawn),
-            WinnerPool::Pool3 => (WinnerPool::Pool3, round.pool3_count, round.pool3_winners_drawn),
-            WinnerPool::Pool4 => (WinnerPool::Pool4, round.pool4_count, round.pool4_winners_drawn),
-            WinnerPool::Complete => return Err("All winners already drawn".to_string()),
-        };
-        
-        if winners_drawn >= winners_count {
-            return Err("Current pool complete, should not happen".to_string());
-        }
-        
-        // Get all winning tickets already drawn for this round
-        let existing_winners = self.winning_tickets.indices().await
-            .map_err(|e: ViewError| format!("Failed to get winning ticket indices: {:?}", e))?
-            .into_iter()
-            .filter(|(rid, _)| *rid == round_id)
-            .map(|(_, ticket)| ticket)
-            .collect::<std::collections::HashSet<_>>();
+        // Determine the tier this winner is drawn for
+        let tier_index = round.current_tier.ok_or("All winners already drawn")? as usize;
+        let winners_count = round.tier_winner_counts[tier_index];
         
         // Select a random ticket that hasn't won yet
-        let mut attempts = 0u64;
-        let max_attempts = round.total_tickets_sold * 2;
-        let selected_ticket;
-        
-        loop {
-            if attempts >= max_attempts {
-                return Err("Failed to find unique winning ticket after many attempts".to_string());
-            }
-            
-            // Generate pseudo-random ticket number using VRF value + attempts
-            let seed = vrf_value.wrapping_add(attempts);
-            let ticket = (seed % round.total_tickets_sold) + 1; // Tickets are 1-indexed
-            
-            if !existing_winners.contains(&ticket) {
-                selected_ticket = ticket;
-                break;
-            }
-            
-            attempts += 1;
-        }
+        let draw_index = round.winners_drawn();
+        let vrf_value = lottery_abi::draw_value(draw_seed, draw_index);
+        let position = lottery_abi::draw_position(vrf_value, draw_index, round.total_tickets_sold)
+            .ok_or("No tickets left to draw")?;
+        let selected_ticket = self.ticket_at_position(round_id, position).await?;
+        let replacement = self.ticket_at_position(round_id, draw_index).await?;
+        self.draw_positions.insert(&(round_id, position), replacement)
+            .map_err(|e: ViewError| format!("Failed to record draw position: {:?}", e))?;
+        // Positions below the next draw index are never read again
+        self.draw_positions.remove(&(round_id, draw_index))
+            .map_err(|e: ViewError| format!("Failed to remove draw position: {:?}", e))?;
         
-        // Get ticket owner
-        let owner = self.ticket_to_owner.get(&(round_id, selected_ticket)).await
-            .map_err(|e: ViewError| format!("Failed to get ticket owner: {:?}", e))?
+        // Get ticket owner and source chain ID from the purchase the ticket belongs to
+        let purchase = self.find_ticket_purchase(round_id, selected_ticket).await?
             .ok_or("Ticket has no owner")?;
-        
-        // Get source chain ID from ticket purchase
-        let source_chain_id = self.ticket_purchases.get(&(round_id, owner.clone())).await
-            .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))?
-            .and_then(|purchase| purchase.source_chain_id.clone());
+        let owner = purchase.owner;
+        let source_chain_id = purchase.source_chain_id;
         
         // Calculate prize for this winner
-        let prize_amount = calculate_prize_per_winner(round.prize_pool, pool, winners_count);
+        let prize_amount = calculate_prize_per_winner(round.prize_pool, &round.prize_tiers[tier_index], winners_count);
         
         // Record winning ticket
         self.winning_tickets.insert(&(round_id, selected_ticket), (owner.clone(), prize_amount, false, source_chain_id.clone()))
             .map_err(|e: ViewError| format!("Failed to record winning ticket: {:?}", e))?;
+        self.draw_records.insert(&(round_id, draw_index), DrawRecord {
+            draw_index,
+            value: vrf_value,
+            position,
+            ticket_number: selected_ticket,
+            owner,
+        }).map_err(|e: ViewError| format!("Failed to record draw: {:?}", e))?;
+        self.draw_counts.insert(&round_id, draw_index + 1)
+            .map_err(|e: ViewError| format!("Failed to update draw count: {:?}", e))?;
+        let mut summary = self.round_summaries.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round summary: {:?}", e))?
+            .unwrap_or_else(|| round_summary(&round));
+        summary.draw_seed = Some(draw_seed);
+        summary.total_prizes = summary.total_prizes.saturating_add(prize_amount);
+        self.round_summaries.insert(&round_id, summary)
+            .map_err(|e: ViewError| format!("Failed to update round summary: {:?}", e))?;
+        let winner_count = self.summary_winner_counts.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get summary winner count: {:?}", e))?
+            .unwrap_or_default();
+        self.summary_winners.insert(&(round_id, winner_count), RoundWinnerSummary {
+            tier: tier_index as u64,
+            ticket_number: selected_ticket,
+            owner,
+            prize_amount,
+        }).map_err(|e: ViewError| format!("Failed to record summary winner: {:?}", e))?;
+        self.summary_winner_counts.insert(&round_id, winner_count + 1)
+            .map_err(|e: ViewError| format!("Failed to update summary winner count: {:?}", e))?;
         
-        // Update round progress
-        match pool {
-            WinnerPool::Pool1 => round.pool1_winners_drawn += 1,
-            WinnerPool::Pool2 => round.pool2_winners_drawn += 1,
-            WinnerPool::Pool3 => round.pool3_winners_drawn += 1,
-            WinnerPool::Pool4 => round.pool4_winners_drawn += 1,
-            WinnerPool::Complete => {},
-        }
+        // Update round progress and advance to the next tier with winners left
+        round.tier_winners_drawn[tier_index] += 1;
+        round.current_tier = round.next_tier();
         
-        // Check if current pool is complete and advance to next pool
+        // If all tiers are complete, mark the round complete and automatically create a new round with the same tiers
         let mut new_round_created = false;
-        let current_pool_complete = match pool {
-            WinnerPool::Pool1 => round.pool1_winners_drawn >= round.pool1_count,
-            WinnerPool::Pool2 => round.pool2_winners_drawn >= round.pool2_count,
-            WinnerPool::Pool3 => round.pool3_winners_drawn >= round.pool3_count,
-            WinnerPool::Pool4 => round.pool4_winners_drawn >= round.pool4_count,
-            WinnerPool::Complete => false,
-        };
-        
-        if current_pool_complete {
-            // Advance to next pool
-            round.current_winner_pool = match pool {
-                WinnerPool::Pool1 => WinnerPool::Pool2,
-                WinnerPool::Pool2 => WinnerPool::Pool3,
-                WinnerPool::Pool3 => WinnerPool::Pool4,
-                WinnerPool::Pool4 => {
-                    // All pools complete, mark round as complete
-                    round.status = RoundStatus::Complete;
-                    WinnerPool::Complete
-                },
-                WinnerPool::Complete => WinnerPool::Complete,
-            };
-            
-            // If all pools complete, automatically create new round
-            if round.current_winner_pool == WinnerPool::Complete {
-                let new_round_id = self.create_lottery_round(default_ticket_price, current_timestamp).await?;
-                new_round_created = true;
-                eprintln!("All winners drawn for round {}. Created new round {} at timestamp {}", round_id, new_round_id, current_timestamp);
-            }
+        if round.current_tier.is_none() {
+            round.status = RoundStatus::Complete;
+            let new_round_id = self.create_lottery_round(default_ticket_price, current_timestamp, None, round.prize_tiers.clone()).await?;
+            new_round_created = true;
+            eprintln!("All winners drawn for round {}. Created new round {} at timestamp {}", round_id, new_round_id, current_timestamp);
         }
         
         // Save updated round
-        self.rounds.insert(&round_id, round)
+        self.lottery_rounds.insert(&round_id, round)
             .map_err(|e: ViewError| format!("Failed to update round: {:?}", e))?;
         
         Ok((round_id, selected_ticket, owner, prize_amount, new_round_created, source_chain_id))
     }
     
+    /// Draw up to `max_count` winners, stopping early once every tier is complete
+    /// Returns: (winners as (ticket_number, owner, prize_amount, source_chain_id), new_round_created)
+    pub async fn draw_winners(&mut self, draw_seed: CryptoHash, round_id: u64, max_count: u64, current_timestamp: u64, default_ticket_price: Amount) -> Result<(Vec<(u64, AccountOwner, Amount, Option<String>)>, bool), String> {
+        let mut winners = Vec::new();
+        for _ in 0..max_count {
+            let (_, ticket_number, owner, prize_amount, new_round_created, source_chain_id) =
+                self.generate_winner(draw_seed, round_id, current_timestamp, default_ticket_price).await?;
+            winners.push((ticket_number, owner, prize_amount, source_chain_id));
+            if new_round_created {
+                return Ok((winners, true));
+            }
+        }
+        Ok((winners, false))
+    }
+    
+    /// First ticket of the `index`-th purchase of a round
+    async fn purchase_first_ticket(&self, round_id: u64, index: u64) -> Result<u64, String> {
+        let record = self.purchase_records.get(&(round_id, index)).await
+            .map_err(|e: ViewError| format!("Failed to get purchase record: {:?}", e))?
+            .ok_or("Purchase record not found")?;
+        Ok(record.first_ticket)
+    }
+    
+    /// Find the purchase a ticket belongs to by binary searching the round's purchase ranges, one read per step
+    pub async fn find_ticket_purchase(&self, round_id: u64, ticket_number: u64) -> Result<Option<TicketPurchase>, String> {
+        let mut low = 0;
+        let mut high = self.get_purchase_count(round_id).await?;
+        let mut found = None;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let first_ticket = self.purchase_first_ticket(round_id, middle).await?;
+            if first_ticket <= ticket_number {
+                found = Some(first_ticket);
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        let Some(first_ticket) = found else {
+            return Ok(None);
+        };
+        let purchase = self.purchases.get(&(round_id, first_ticket)).await
+            .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))?;
+        Ok(purchase.filter(|purchase| ticket_number <= purchase.last_ticket))
+    }
+    
+    /// Ticket currently at `position` of a round's draw shuffle
+    async fn ticket_at_position(&self, round_id: u64, position: u64) -> Result<u64, String> {
+        Ok(self.draw_positions.get(&(round_id, position)).await
+            .map_err(|e: ViewError| format!("Failed to get draw position: {:?}", e))?
+            .unwrap_or(position + 1))
+    }
+    
     /// Mark winning ticket as claimed
     pub async fn mark_prize_claimed(&mut self, round_id: u64, ticket_number: u64) -> Result<(), String> {
         let winning_info = self.winning_tickets.get(&(round_id, ticket_number)).await
@@ -459,21 +1057,33 @@ impl LotteryRoundsState {
         Ok(())
     }
     
-    /// Get lottery round by ID
+    /// Get lottery round by ID, showing a legacy round with the default prize tiers
     pub async fn get_round(&self, round_id: u64) -> Result<Option<LotteryRound>, String> {
-        self.rounds.get(&round_id).await
-            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))
+        if let Some(round) = self.lottery_rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))? {
+            return Ok(Some(round));
+        }
+        Ok(self.rounds.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get legacy round: {:?}", e))?
+            .map(|legacy| legacy.to_round()))
     }
     
-    /// Get all lottery rounds
+    /// Get all lottery rounds, legacy rounds first
     pub async fn get_all_rounds(&self) -> Result<Vec<LotteryRound>, String> {
-        let indices = self.rounds.indices().await
-            .map_err(|e: ViewError| format!("Failed to get round indices: {:?}", e))?;
+        let mut rounds = Vec::new();
+        for index in self.rounds.indices().await
+            .map_err(|e: ViewError| format!("Failed to get legacy round indices: {:?}", e))? {
+            if let Some(legacy) = self.rounds.get(&index).await
+                .map_err(|e: ViewError| format!("Failed to get legacy round: {:?}", e))? {
+                rounds.push(legacy.to_round());
+            }
+        }
         
-        let mut rounds = Vec::with_capacity(indices.len());
+        let indices = self.lottery_rounds.indices().await
+            .map_err(|e: ViewError| format!("Failed to get round indices: {:?}", e))?;
         
         for index in indices {
-            if let Some(round) = self.rounds.get(&index).await
+            if let Some(round) = self.lottery_rounds.get(&index).await
                 .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))? {
                 rounds.push(round);
             }
@@ -481,33 +1091,66 @@ impl LotteryRoundsState {
         Ok(rounds)
     }
     
+    /// Get the permanent summary of a round, with its winners in draw order
+    pub async fn get_round_summary(&self, round_id: u64) -> Result<Option<LotteryRoundSummary>, String> {
+        let Some(mut summary) = self.round_summaries.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get round summary: {:?}", e))? else {
+            return Ok(None);
+        };
+        
+        // Summaries written before winners had their own entries already hold them
+        let winner_count = self.summary_winner_counts.get(&round_id).await
+            .map_err(|e: ViewError| format!("Failed to get summary winner count: {:?}", e))?
+            .unwrap_or_default();
+        for index in 0..winner_count {
+            if let Some(winner) = self.summary_winners.get(&(round_id, index)).await
+                .map_err(|e: ViewError| format!("Failed to get summary winner: {:?}", e))? {
+                summary.winners.push(winner);
+            }
+        }
+        Ok(Some(summary))
+    }
+    
+    /// Get the summaries of all rounds, including cleaned up ones
+    pub async fn get_round_summaries(&self) -> Result<Vec<LotteryRoundSummary>, String> {
+        let indices = self.round_summaries.indices().await
+            .map_err(|e: ViewError| format!("Failed to get round summary indices: {:?}", e))?;
+        
+        let mut summaries = Vec::with_capacity(indices.len());
+        
+        for index in indices {
+            if let Some(summary) = self.get_round_summary(index).await? {
+                summaries.push(summary);
+            }
+        }
+        Ok(summaries)
+    }
+    
     /// Get active round ID
     pub async fn get_active_round(&self) -> Result<Option<u64>, String> {
         Ok(*self.active_round.get())
     }
     
-    /// Get ticket purchases for a specific round
+    /// Get every ticket purchase of a specific round, in ticket order
     pub async fn get_round_ticket_purchases(&self, round_id: u64) -> Result<Vec<(AccountOwner, TicketPurchase)>, String> {
-        let indices = self.ticket_purchases.indices().await
-            .map_err(|e: ViewError| format!("Failed to get ticket purchase indices: {:?}", e))?;
+        let purchase_count = self.get_purchase_count(round_id).await?;
         
-        let mut purchases = Vec::new();
+        let mut purchases = Vec::with_capacity(purchase_count as usize);
         
-        for (rid, owner) in indices {
-            if rid == round_id {
-                if let Some(purchase) = self.ticket_purchases.get(&(rid, owner.clone())).await
-                    .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))? {
-                    purchases.push((owner, purchase));
-                }
+        for index in 0..purchase_count {
+            let first_ticket = self.purchase_first_ticket(round_id, index).await?;
+            if let Some(purchase) = self.purchases.get(&(round_id, first_ticket)).await
+                .map_err(|e: ViewError| format!("Failed to get ticket purchase: {:?}", e))? {
+                purchases.push((purchase.owner, purchase));
             }
         }
         
         Ok(purchases)
     }
     
-    /// Get user's tickets for a specific round
-    pub async fn get_user_tickets(&self, round_id: u64, owner: AccountOwner) -> Result<Option<TicketPurchase>, String> {
-        self.ticket_purchases.get(&(round_id, owner)).await
+    /// Get user's tickets for a specific round, across all their purchases
+    pub async fn get_user_tickets(&self, round_id: u64, owner: AccountOwner) -> Result<Option<OwnerTickets>, String> {
+        self.owner_tickets.get(&(round_id, owner)).await
             .map_err(|e: ViewError| format!("Failed to get user tickets: {:?}", e))
     }
     
@@ -533,8 +1176,7 @@ impl LotteryRoundsState {
     /// Get the current round's ticket price
     pub async fn get_current_ticket_price(&self) -> Result<Amount, String> {
         if let Some(round_id) = *self.active_round.get() {
-            if let Some(round) = self.rounds.get(&round_id).await
-                .map_err(|e: ViewError| format!("Failed to get round: {:?}", e))? {
+            if let Some(round) = self.get_round(round_id).await? {
                 return Ok(round.ticket_price);
             }
         }
@@ -6,11 +6,17 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::{ApplicationId, ChainId, WithContractAbi},
+    abis::fungible::{FungibleOperation, FungibleResponse, FungibleTokenAbi},
+    linera_base_types::{AccountOwner, Amount, ApplicationId, ChainId, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use microbetreal::{Message, MicrobetAbi, ExtendedOperation, ExtendedResponse, Prediction};
+use lottery_abi::{LotteryRoundsAbi, LotteryRoundsOperation, LotteryRoundsResponse};
+use microbetreal::{
+    Message, MicrobetAbi, BetReceipt, ExtendedOperation, ExtendedResponse, GameAbi, GameInfo, GameKind,
+    AutoBetConfig, GameOperation, GameResponse, PendingBet, Prediction, QueuedBetOutcome, RewardRecord, Session,
+    StrategyKind,
+};
 use self::state::MicrobetState;
 
 // Conversion function
@@ -21,6 +27,60 @@ fn to_rounds_prediction(pred: Prediction) -> rounds::Prediction {
     }
 }
 
+fn from_rounds_prediction(pred: rounds::Prediction) -> Prediction {
+    match pred {
+        rounds::Prediction::Up => Prediction::Up,
+        rounds::Prediction::Down => Prediction::Down,
+    }
+}
+
+fn from_rounds_receipt(receipt: rounds::BetReceipt) -> BetReceipt {
+    BetReceipt {
+        round_id: receipt.round_id,
+        owner: receipt.owner,
+        prediction: from_rounds_prediction(receipt.prediction),
+        amount: receipt.amount,
+        total_stake: receipt.total_stake,
+        sequence: receipt.sequence,
+    }
+}
+
+fn to_rounds_config(config: AutoBetConfig) -> rounds::AutoBetConfig {
+    rounds::AutoBetConfig {
+        kind: match config.kind {
+            StrategyKind::FixedSide => rounds::StrategyKind::FixedSide,
+            StrategyKind::Alternate => rounds::StrategyKind::Alternate,
+            StrategyKind::FollowLastResult => rounds::StrategyKind::FollowLastResult,
+            StrategyKind::Martingale => rounds::StrategyKind::Martingale,
+        },
+        prediction: to_rounds_prediction(config.prediction),
+        base_amount: config.base_amount,
+        max_amount: config.max_amount,
+        budget: config.budget,
+        loss_limit: config.loss_limit,
+    }
+}
+
+/// Game id under which the configured Rounds app can be played without registering it
+const DEFAULT_GAME_ID: &str = "rounds";
+
+/// Parse the selection of a prediction rounds game ("Up" / "Down")
+fn parse_prediction(selection: Option<&str>) -> Prediction {
+    match selection.map(|selection| selection.to_ascii_lowercase()).as_deref() {
+        Some("up") => Prediction::Up,
+        Some("down") => Prediction::Down,
+        _ => panic!("Prediction rounds games need a selection of \"Up\" or \"Down\""),
+    }
+}
+
+/// Slippage conditions attached to a bet
+#[derive(Clone, Copy, Default)]
+struct BetConditions {
+    expected_round_id: Option<u64>,
+    min_payout_multiplier: Option<u64>,
+    deadline: Option<u64>,
+}
+
 pub struct MicrobetContract {
     state: MicrobetState,
     runtime: ContractRuntime<Self>,
@@ -68,146 +128,1171 @@ impl Contract for MicrobetContract {
                 amount,
                 target_account,
                 prediction: Some(prediction),
+                rollover,
+                expected_round_id,
+                min_payout_multiplier,
+                deadline,
             } => {
                 // Transfer with prediction - this is our main betting operation
-                
+                // (only the chain of `target_account` is used - the stake is always custodied by the app)
+                let conditions = BetConditions { expected_round_id, min_payout_multiplier, deadline };
+                self.bet_with_stake(owner, amount, target_account.chain_id, prediction, rollover, conditions).await
+            }
 
-                
-                let params = self.runtime.application_parameters();
-                let native_app_id = params.native_app_id.with_abi::<native::NativeAbi>();
-                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+            ExtendedOperation::Claim { source_account, amount, target_account, prediction: Some(prediction) } => {
+                // Bet funded by tokens held on another chain
+                self.runtime
+                    .check_account_permission(source_account.owner)
+                    .expect("Permission for Claim operation");
 
-                // Step 1: Call Native app to transfer tokens
-                let _native_response: native::NativeResponse = self.runtime.call_application(
-                    true,
-                    native_app_id,
-                    &native::NativeOperation::Transfer {
-                        owner,
+                if source_account.chain_id == self.runtime.chain_id() {
+                    // Nothing to claim - the tokens are already here
+                    self.bet_with_stake(
+                        source_account.owner,
                         amount,
-                        target_account,
-                    },
+                        target_account.chain_id,
+                        prediction,
+                        false,
+                        BetConditions::default(),
+                    ).await
+                } else {
+                    // Ask Microbetreal on the source chain to stake the tokens on the owner's behalf
+                    self.runtime
+                        .prepare_message(Message::ClaimWithPrediction {
+                            source_owner: source_account.owner,
+                            amount,
+                            target_chain_id: target_account.chain_id,
+                            prediction,
+                        })
+                        .with_authentication()
+                        .send_to(source_account.chain_id);
+                    ExtendedResponse::Ok
+                }
+            }
+
+            ExtendedOperation::SendReward { recipient, amount, source_chain_id, round_id, memo, currency_app_id } => {
+                // Called by Rounds or a registered game to distribute rewards from the escrow
+                self.debit_caller_escrow(currency_app_id, amount).await;
+                let escrow = self.escrow_owner();
+
+                let target_chain = self.reward_target_chain(&source_chain_id);
+                let memo = memo.or_else(|| round_id.map(|id| format!("round:{}:payout", id)));
+                self.pay_out(currency_app_id, escrow, vec![(recipient, amount, target_chain)], memo);
+
+                // Let the winner's chain know where the payment came from
+                self.notify_reward(recipient, amount, round_id, target_chain).await;
+
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::SendRewards { rewards, round_id, memo, currency_app_id } => {
+                // Called by Rounds or a registered game to pay all winners of a round in one call from the escrow
+                let total = rewards.iter().fold(Amount::ZERO, |total, reward| total.saturating_add(reward.amount));
+                self.debit_caller_escrow(currency_app_id, total).await;
+                let escrow = self.escrow_owner();
+
+                let payouts: Vec<_> = rewards.iter().map(|reward| {
+                    (reward.recipient, reward.amount, self.reward_target_chain(&reward.source_chain_id))
+                }).collect();
+                let memo = memo.or_else(|| round_id.map(|id| format!("round:{}:payout", id)));
+                self.pay_out(currency_app_id, escrow, payouts.clone(), memo);
+
+                for (recipient, amount, target_chain) in payouts {
+                    self.notify_reward(recipient, amount, round_id, target_chain).await;
+                }
+
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::RegisterGame { game_id, app_id, kind } => {
+                self.check_registry_admin();
+                let currency_app_id = self.game_currency(app_id, kind);
+                let game = GameInfo {
+                    game_id,
+                    app_id,
+                    kind,
+                    currency_app_id,
+                    registered_at: self.runtime.system_time().micros(),
+                };
+                self.state.register_game(game).await
+                    .unwrap_or_else(|e| panic!("Failed to register game: {}", e));
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::UnregisterGame { game_id } => {
+                self.check_registry_admin();
+                self.state.unregister_game(&game_id).await
+                    .unwrap_or_else(|e| panic!("Failed to unregister game: {}", e));
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::CreditLegacyRoundsEscrow { amount } => {
+                self.check_registry_admin();
+                assert!(
+                    !*self.state.legacy_rounds_escrow_credited.get(),
+                    "Legacy Rounds escrow was already credited"
+                );
+                let params = self.runtime.application_parameters();
+                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+                // Only tokens of the escrow that no game accounts for can be untracked Rounds stakes
+                let currency = self.market_currency(rounds_app_id);
+                let escrow = self.escrow_owner();
+                let balance = match self.runtime.call_application(true, currency, &FungibleOperation::Balance { owner: escrow }) {
+                    FungibleResponse::Balance(balance) => balance,
+                    _ => panic!("Unexpected response to Balance"),
+                };
+                let credited = self.state.total_game_escrow(currency.forget_abi()).await
+                    .expect("Failed to get game escrow");
+                let unassigned = balance.saturating_sub(credited);
+                assert!(
+                    amount <= unassigned,
+                    "Only {} of the escrow is not assigned to a game",
+                    unassigned
                 );
+                self.credit_rounds_stake(rounds_app_id, amount).await;
+                self.state.legacy_rounds_escrow_credited.set(true);
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::CreateSession { owner, session_key, max_total, max_per_bet, expires_at } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for CreateSession operation");
+                let now = self.runtime.system_time().micros();
+                let session = Session {
+                    session_key,
+                    max_total,
+                    max_per_bet,
+                    spent: Amount::ZERO,
+                    expires_at,
+                    created_at: now,
+                };
+                self.state.add_session(owner, session, now).await
+                    .unwrap_or_else(|e| panic!("Failed to create session: {}", e));
+                self.approve_session_budget(owner).await;
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::RevokeSession { owner, session_key } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for RevokeSession operation");
+                self.state.revoke_session(owner, session_key).await
+                    .unwrap_or_else(|e| panic!("Failed to revoke session: {}", e));
+                self.approve_session_budget(owner).await;
+                ExtendedResponse::Ok
+            }
 
-                // Step 2: Place bet in Rounds app
+            ExtendedOperation::CreateStrategy { owner, target_account, config } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for CreateStrategy operation");
+                let params = self.runtime.application_parameters();
+                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+                let currency = self.market_currency(rounds_app_id);
+
+                // Step 1: Move the whole budget into the escrow on the Rounds chain
+                let escrow_account = linera_sdk::abis::fungible::Account {
+                    chain_id: target_account.chain_id,
+                    owner: self.escrow_owner(),
+                };
+                self.fund_stake(currency, owner, config.budget, escrow_account);
+
+                // Step 2: Register the strategy in Rounds
                 if target_account.chain_id == self.runtime.chain_id() {
-                    // Same chain - no source_chain_id needed
-                    let _rounds_response: rounds::RoundsResponse = self.runtime.call_application(
-                        true,
-                        rounds_app_id,
-                        &rounds::RoundsOperation::PlaceBet {
-                            owner, // Sender makes the bet
-                            amount,
-                            prediction: to_rounds_prediction(prediction),
-                            source_chain_id: None,
-                        },
-                    );
+                    let strategy_id = self.register_strategy(owner, None, config).await;
+                    ExtendedResponse::StrategyId(strategy_id)
                 } else {
-                    // Cross-chain - send message with SENDER'S chain_id
-                    let message = Message::TransferWithPrediction {
-                        owner: target_account.owner,
-                        amount,
-                        prediction,
-                        source_chain_id: self.runtime.chain_id().to_string(), // SENDER'S chain!
+                    let message = Message::RegisterStrategy {
+                        config,
+                        source_chain_id: self.runtime.chain_id().to_string(),
                         source_owner: owner,
                     };
                     self.runtime
                         .prepare_message(message)
                         .with_authentication()
                         .send_to(target_account.chain_id);
+                    ExtendedResponse::Ok
                 }
+            }
 
+            ExtendedOperation::CancelStrategy { owner, target_account, strategy_id } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for CancelStrategy operation");
+                if target_account.chain_id == self.runtime.chain_id() {
+                    self.cancel_strategy(owner, strategy_id).await;
+                } else {
+                    self.runtime
+                        .prepare_message(Message::CancelStrategy { strategy_id, source_owner: owner })
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                }
                 ExtendedResponse::Ok
             }
 
-            ExtendedOperation::SendReward { recipient, amount, source_chain_id } => {
-                // Called by Rounds to distribute rewards
+            ExtendedOperation::Follow { owner, target_account, leader, ratio_bps, max_per_bet, budget } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for Follow operation");
                 let params = self.runtime.application_parameters();
-                let native_app_id = params.native_app_id.with_abi::<native::NativeAbi>();
+                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+                let currency = self.market_currency(rounds_app_id);
+
+                // Step 1: Move the whole budget into the escrow on the Rounds chain
+                let escrow_account = linera_sdk::abis::fungible::Account {
+                    chain_id: target_account.chain_id,
+                    owner: self.escrow_owner(),
+                };
+                self.fund_stake(currency, owner, budget, escrow_account);
+
+                // Step 2: Subscribe to the leader in Rounds
+                if target_account.chain_id == self.runtime.chain_id() {
+                    let follow_id = self.follow(owner, None, leader, ratio_bps, max_per_bet, budget).await
+                        .unwrap_or_else(|e| panic!("Failed to follow: {}", e));
+                    ExtendedResponse::FollowId(follow_id)
+                } else {
+                    let message = Message::Follow {
+                        leader,
+                        ratio_bps,
+                        max_per_bet,
+                        budget,
+                        source_chain_id: self.runtime.chain_id().to_string(),
+                        source_owner: owner,
+                    };
+                    self.runtime
+                        .prepare_message(message)
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                    ExtendedResponse::Ok
+                }
+            }
 
-                let target_chain = if let Some(source_chain_id_str) = &source_chain_id {
-                    source_chain_id_str.parse::<ChainId>().unwrap_or_else(|_| self.runtime.chain_id())
+            ExtendedOperation::Unfollow { owner, target_account, follow_id } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for Unfollow operation");
+                if target_account.chain_id == self.runtime.chain_id() {
+                    self.unfollow(owner, follow_id).await;
                 } else {
-                    self.runtime.chain_id()
+                    self.runtime
+                        .prepare_message(Message::Unfollow { follow_id, source_owner: owner })
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                }
+                ExtendedResponse::Ok
+            }
+
+            ExtendedOperation::PlaceParlay { owner, target_account, amount, predictions } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for PlaceParlay operation");
+                let params = self.runtime.application_parameters();
+                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+                let currency = self.market_currency(rounds_app_id);
+
+                // Step 1: Move the stake into the escrow on the Rounds chain
+                let escrow_account = linera_sdk::abis::fungible::Account {
+                    chain_id: target_account.chain_id,
+                    owner: self.escrow_owner(),
                 };
+                self.fund_stake(currency, owner, amount, escrow_account);
 
-                let target_account = linera_sdk::abis::fungible::Account {
-                    chain_id: target_chain,
-                    owner: recipient,
+                // Step 2: Place the parlay in Rounds
+                if target_account.chain_id == self.runtime.chain_id() {
+                    let parlay_id = self.place_parlay(owner, None, amount, predictions).await
+                        .unwrap_or_else(|e| panic!("Failed to place parlay: {}", e));
+                    ExtendedResponse::ParlayId(parlay_id)
+                } else {
+                    let message = Message::PlaceParlay {
+                        amount,
+                        predictions,
+                        source_chain_id: self.runtime.chain_id().to_string(),
+                        source_owner: owner,
+                    };
+                    self.runtime
+                        .prepare_message(message)
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                    ExtendedResponse::Ok
+                }
+            }
+
+            ExtendedOperation::FundParlayPool { owner, target_account, amount } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for FundParlayPool operation");
+                let params = self.runtime.application_parameters();
+                let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+                let currency = self.market_currency(rounds_app_id);
+
+                let escrow_account = linera_sdk::abis::fungible::Account {
+                    chain_id: target_account.chain_id,
+                    owner: self.escrow_owner(),
                 };
+                self.fund_stake(currency, owner, amount, escrow_account);
 
-                let resolver_owner = self.runtime.authenticated_signer()
-                    .expect("Authentication required for reward distribution");
+                if target_account.chain_id == self.runtime.chain_id() {
+                    self.fund_parlay_pool(amount).await;
+                } else {
+                    self.runtime
+                        .prepare_message(Message::FundParlayPool { amount })
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                }
+                ExtendedResponse::Ok
+            }
 
-                let _native_response: native::NativeResponse = self.runtime.call_application(
-                    true,
-                    native_app_id,
-                    &native::NativeOperation::Transfer {
-                        owner: resolver_owner,
-                        amount,
-                        target_account,
-                    },
+            ExtendedOperation::SettleQueuedBets { bets } => {
+                let params = self.runtime.application_parameters();
+                assert_eq!(
+                    self.runtime.authenticated_caller_id(),
+                    Some(params.rounds_app_id),
+                    "Only the Rounds app can settle queued bets"
                 );
-
+                for bet in bets {
+                    self.settle_queued_bet(bet).await;
+                }
                 ExtendedResponse::Ok
             }
 
+            ExtendedOperation::Play { owner, amount, target_account, game_id, selection } => {
+                let game = self.resolve_game(&game_id).await;
+
+                // Step 1: Move the stake into the escrow of this application on the game's chain
+                let escrow_account = linera_sdk::abis::fungible::Account {
+                    chain_id: target_account.chain_id,
+                    owner: self.escrow_owner(),
+                };
+                self.fund_stake(game.currency_app_id.with_abi::<FungibleTokenAbi>(), owner, amount, escrow_account);
+
+                // Step 2: Hand the stake to the game
+                if target_account.chain_id == self.runtime.chain_id() {
+                    self.accept_stake(&game, owner, amount, None, selection).await
+                } else {
+                    let message = Message::StakeForGame {
+                        game_id,
+                        amount,
+                        source_chain_id: self.runtime.chain_id().to_string(),
+                        source_owner: owner,
+                        selection,
+                    };
+                    self.runtime
+                        .prepare_message(message)
+                        .with_authentication()
+                        .send_to(target_account.chain_id);
+                    ExtendedResponse::Ok
+                }
+            }
+
             // Pass-through operations to Native app
-            ExtendedOperation::Transfer { owner, amount, target_account, prediction: None } => {
+            ExtendedOperation::Transfer { owner, amount, target_account, prediction: None, .. } => {
                 // Regular transfer without prediction - pass to Native
-                let params = self.runtime.application_parameters();
-                let native_app_id = params.native_app_id.with_abi::<native::NativeAbi>();
-
-                let _response: native::NativeResponse = self.runtime.call_application(
-                    true,
-                    native_app_id,
-                    &native::NativeOperation::Transfer { owner, amount, target_account },
-                );
+                self.call_native(native::NativeOperation::Transfer { owner, amount, target_account });
                 ExtendedResponse::Ok
             }
 
             ExtendedOperation::Claim { source_account, amount, target_account, prediction: None } => {
                 // Regular claim without prediction - pass to Native
-                let params = self.runtime.application_parameters();
-                let native_app_id = params.native_app_id.with_abi::<native::NativeAbi>();
+                self.call_native(native::NativeOperation::Claim { source_account, amount, target_account });
+                ExtendedResponse::Ok
+            }
 
-                let _response: native::NativeResponse = self.runtime.call_application(
-                    true,
-                    native_app_id,
-                    &native::NativeOperation::Claim { source_account, amount, target_account },
-                );
+            // Remaining operations are passed through to Native, which performs the permission checks
+            ExtendedOperation::Balance { owner } => {
+                match self.call_native(native::NativeOperation::Balance { owner }) {
+                    native::NativeResponse::Balance(balance) => ExtendedResponse::Balance(balance),
+                    response => panic!("Unexpected response to Balance: {:?}", response),
+                }
+            }
+
+            ExtendedOperation::ChainBalance => {
+                match self.call_native(native::NativeOperation::ChainBalance) {
+                    native::NativeResponse::ChainBalance(balance) => ExtendedResponse::ChainBalance(balance),
+                    response => panic!("Unexpected response to ChainBalance: {:?}", response),
+                }
+            }
+
+            ExtendedOperation::TickerSymbol => {
+                match self.call_native(native::NativeOperation::TickerSymbol) {
+                    native::NativeResponse::TickerSymbol(symbol) => ExtendedResponse::TickerSymbol(symbol),
+                    response => panic!("Unexpected response to TickerSymbol: {:?}", response),
+                }
+            }
+
+            ExtendedOperation::Withdraw => {
+                // Native withdraws the balance of the signer
+                self.runtime
+                    .authenticated_signer()
+                    .expect("Withdraw requires an authenticated signer");
+                self.call_native(native::NativeOperation::Withdraw);
                 ExtendedResponse::Ok
             }
 
-            _ => {
-                // All other operations: pass through to Native
-                panic!("Operation not supported by Microbetreal - use Native app directly for: {:?}", std::any::type_name_of_val(&operation));
+            ExtendedOperation::Mint { owner, amount } => {
+                // Native only accepts mints from its configured minters
+                self.call_native(native::NativeOperation::Mint { owner, amount });
+                ExtendedResponse::Ok
             }
         }
     }
 
     async fn execute_message(&mut self, message: Self::Message) {
         match message {
-            Message::TransferWithPrediction { owner: _, amount, prediction, source_chain_id, source_owner } => {
+            Message::TransferWithPrediction {
+                owner: _,
+                amount,
+                prediction,
+                source_chain_id,
+                source_owner,
+                rollover,
+                expected_round_id,
+                min_payout_multiplier,
+                deadline,
+            } => {
                 // Handle cross-chain transfer with prediction
                 // Place bet for source owner with SENDER'S chain_id
                 let params = self.runtime.application_parameters();
                 let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
-                
-                let _response: rounds::RoundsResponse = self.runtime.call_application(
+                let active_round = self.active_round(rounds_app_id);
+
+                // Check slippage conditions here so a rejected bet is refunded instead of failing the message
+                let now = self.runtime.system_time().micros();
+                let rejection = active_round.as_ref().and_then(|round| rounds::check_bet_conditions(
+                    round,
+                    amount,
+                    to_rounds_prediction(prediction),
+                    expected_round_id,
+                    min_payout_multiplier,
+                    deadline,
+                    now,
+                ).err());
+
+                if active_round.is_some() && rejection.is_none() {
+                    let response: rounds::RoundsResponse = self.runtime.call_application(
+                        true,
+                        rounds_app_id,
+                        &rounds::RoundsOperation::PlaceBet {
+                            owner: source_owner,
+                            amount,
+                            prediction: to_rounds_prediction(prediction),
+                            source_chain_id: Some(source_chain_id.clone()), // Use SENDER'S chain from message!
+                            expected_round_id,
+                            min_payout_multiplier,
+                            deadline,
+                        },
+                    );
+                    self.credit_rounds_stake(rounds_app_id, amount).await;
+                    
+                    // Confirm the bet back on the bettor's chain
+                    if let rounds::RoundsResponse::BetReceipt(receipt) = response {
+                        let source_chain = source_chain_id.parse::<ChainId>()
+                            .unwrap_or_else(|_| self.runtime.chain_id());
+                        self.runtime
+                            .prepare_message(Message::BetConfirmed { receipt: from_rounds_receipt(receipt) })
+                            .with_authentication()
+                            .send_to(source_chain);
+                    }
+                } else if active_round.is_none() && rollover && expected_round_id.is_none() {
+                    // User opted in - keep the stake and bet in the next round
+                    let _response: rounds::RoundsResponse = self.runtime.call_application(
+                        true,
+                        rounds_app_id,
+                        &rounds::RoundsOperation::QueueBet {
+                            owner: source_owner,
+                            amount,
+                            prediction: to_rounds_prediction(prediction),
+                            source_chain_id: Some(source_chain_id),
+                        },
+                    );
+                    self.credit_rounds_stake(rounds_app_id, amount).await;
+                } else {
+                    // No round to bet into, or the bettor's conditions were not met - send the stake back
+                    let memo = match rejection {
+                        Some(reason) => format!("bet:refund:{}", reason),
+                        None => "bet:refund".to_string(),
+                    };
+                    self.refund_bet(amount, &source_chain_id, source_owner, &memo);
+                    
+                    let source_chain = source_chain_id.parse::<ChainId>()
+                        .unwrap_or_else(|_| self.runtime.chain_id());
+                    self.runtime
+                        .prepare_message(Message::BetRefunded { owner: source_owner, amount, prediction })
+                        .with_authentication()
+                        .send_to(source_chain);
+                }
+            }
+            
+            Message::ClaimWithPrediction { source_owner, amount, target_chain_id, prediction } => {
+                // The owner's tokens live on this chain - stake them as if the owner bet from here
+                self.bet_with_stake(
+                    source_owner,
+                    amount,
+                    target_chain_id,
+                    prediction,
+                    false,
+                    BetConditions::default(),
+                ).await;
+            }
+
+            Message::RegisterStrategy { config, source_chain_id, source_owner } => {
+                // The budget already sits in the escrow of this chain
+                self.register_strategy(source_owner, Some(source_chain_id), config).await;
+            }
+
+            Message::CancelStrategy { strategy_id, source_owner } => {
+                self.cancel_strategy(source_owner, strategy_id).await;
+            }
+
+            Message::Follow { leader, ratio_bps, max_per_bet, budget, source_chain_id, source_owner } => {
+                // The budget already sits in the escrow of this chain - send it back if Rounds rejects the subscription
+                if let Err(e) = self.follow(source_owner, Some(source_chain_id.clone()), leader, ratio_bps, max_per_bet, budget).await {
+                    self.refund_bet(budget, &source_chain_id, source_owner, &format!("follow:refund:{}", e));
+                }
+            }
+
+            Message::Unfollow { follow_id, source_owner } => {
+                self.unfollow(source_owner, follow_id).await;
+            }
+
+            Message::PlaceParlay { amount, predictions, source_chain_id, source_owner } => {
+                // The stake already sits in the escrow of this chain - send it back if Rounds rejects the parlay
+                if let Err(e) = self.place_parlay(source_owner, Some(source_chain_id.clone()), amount, predictions).await {
+                    self.refund_bet(amount, &source_chain_id, source_owner, &format!("parlay:refund:{}", e));
+                }
+            }
+
+            Message::FundParlayPool { amount } => {
+                self.fund_parlay_pool(amount).await;
+            }
+
+            Message::StakeForGame { game_id, amount, source_chain_id, source_owner, selection } => {
+                // The stake already sits in the escrow of this chain - only the registry here decides what the game is
+                let game = self.resolve_game(&game_id).await;
+                self.accept_stake(&game, source_owner, amount, Some(source_chain_id), selection).await;
+            }
+
+            Message::BetConfirmed { receipt } => {
+                // Our cross-chain bet landed in a round
+                self.state.confirm_bet(receipt).await
+                    .expect("Failed to record bet confirmation");
+            }
+            
+            Message::BetRefunded { owner, amount, prediction } => {
+                // Our cross-chain bet was rejected and the stake sent back
+                self.state.refund_bet(owner, amount, prediction).await
+                    .expect("Failed to record bet refund");
+            }
+            
+            Message::RewardReceived { recipient, amount, round_id } => {
+                let reward = RewardRecord {
+                    round_id,
+                    amount,
+                    received_at: self.runtime.system_time().micros(),
+                };
+                self.state.add_reward(recipient, reward).await
+                    .expect("Failed to record reward");
+            }
+        }
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl MicrobetContract {
+    /// Account of this application, custodying all stakes until they are paid out
+    fn escrow_owner(&mut self) -> AccountOwner {
+        AccountOwner::from(self.runtime.application_id().forget_abi())
+    }
+
+    /// Escrow payouts can only be made by a game, and only out of the stakes it accepted
+    async fn debit_caller_escrow(&mut self, currency_app_id: Option<ApplicationId>, amount: Amount) {
+        let params = self.runtime.application_parameters();
+        let caller = self.runtime.authenticated_caller_id()
+            .expect("Rewards can only be sent by a game application");
+        let currency = currency_app_id.unwrap_or(params.native_app_id);
+        self.state.debit_game(caller, currency, amount).await
+            .unwrap_or_else(|e| panic!("Failed to send rewards: {}", e));
+    }
+
+    /// Move a stake from `owner` to `escrow_account` (the owner must sign)
+    fn fund_stake(
+        &mut self,
+        currency: ApplicationId<FungibleTokenAbi>,
+        owner: AccountOwner,
+        amount: Amount,
+        escrow_account: linera_sdk::abis::fungible::Account,
+    ) {
+        let operation = FungibleOperation::Transfer {
+            owner,
+            amount,
+            target_account: escrow_account,
+        };
+        let _token_response: FungibleResponse = self.runtime.call_application(true, currency, &operation);
+    }
+
+    /// Move the stake of a Rounds bet from `owner` to `escrow_account`
+    /// When signed by one of the owner's session keys, the bet is charged to that session
+    /// and paid from the allowance the owner granted to this application in the Rounds market currency
+    async fn fund_bet_stake(
+        &mut self,
+        currency: ApplicationId<FungibleTokenAbi>,
+        owner: AccountOwner,
+        amount: Amount,
+        escrow_account: linera_sdk::abis::fungible::Account,
+    ) {
+        let session_key = match self.runtime.authenticated_signer() {
+            Some(signer) if signer != owner => {
+                let is_session_key = self.state.is_session_key(&owner, &signer).await
+                    .expect("Failed to get sessions");
+                is_session_key.then_some(signer)
+            }
+            _ => None,
+        };
+
+        let Some(session_key) = session_key else {
+            self.fund_stake(currency, owner, amount, escrow_account);
+            return;
+        };
+
+        let now = self.runtime.system_time().micros();
+        self.state.spend_session(owner, session_key, amount, now).await
+            .unwrap_or_else(|e| panic!("Session bet rejected: {}", e));
+        let operation = FungibleOperation::TransferFrom {
+            owner,
+            spender: self.escrow_owner(),
+            amount,
+            target_account: escrow_account,
+        };
+        let _token_response: FungibleResponse = self.runtime.call_application(true, currency, &operation);
+    }
+
+    /// Approve this application for the budget the owner's sessions can still spend
+    async fn approve_session_budget(&mut self, owner: AccountOwner) {
+        let params = self.runtime.application_parameters();
+        let currency = self.market_currency(params.rounds_app_id.with_abi::<rounds::RoundsAbi>());
+        let now = self.runtime.system_time().micros();
+        let budget = self.state.session_budget(&owner, now).await
+            .expect("Failed to get session budget");
+        let spender = self.escrow_owner();
+        let _token_response: FungibleResponse = self.runtime.call_application(
+            true,
+            currency,
+            &FungibleOperation::Approve {
+                owner,
+                spender,
+                allowance: budget,
+            },
+        );
+    }
+
+    /// Only the registry admin manages games; without a configured admin the registry is fixed
+    fn check_registry_admin(&mut self) {
+        let admin = self.runtime.application_parameters().admin
+            .expect("No registry admin is configured");
+        assert_eq!(
+            self.runtime.authenticated_signer(),
+            Some(admin),
+            "Only the registry admin can manage games"
+        );
+    }
+
+    /// Look up a game, falling back to the configured Rounds app for the default game id
+    async fn resolve_game(&mut self, game_id: &str) -> GameInfo {
+        if let Some(game) = self.state.get_game(game_id).await.expect("Failed to get game") {
+            return game;
+        }
+        assert_eq!(game_id, DEFAULT_GAME_ID, "Game {} is not registered", game_id);
+        let params = self.runtime.application_parameters();
+        let kind = GameKind::PredictionRounds;
+        GameInfo {
+            game_id: game_id.to_string(),
+            app_id: params.rounds_app_id,
+            kind,
+            currency_app_id: self.game_currency(params.rounds_app_id, kind),
+            registered_at: 0,
+        }
+    }
+
+    /// Token a game takes stakes in
+    fn game_currency(&mut self, app_id: ApplicationId, kind: GameKind) -> ApplicationId {
+        let params = self.runtime.application_parameters();
+        match kind {
+            GameKind::PredictionRounds => self.market_currency(app_id.with_abi::<rounds::RoundsAbi>()).forget_abi(),
+            GameKind::Lottery => params.native_app_id,
+            GameKind::Generic => {
+                let response: GameResponse = self.runtime.call_application(
+                    true,
+                    app_id.with_abi::<GameAbi>(),
+                    &GameOperation::GetCurrency,
+                );
+                match response {
+                    GameResponse::Currency(currency) => currency.unwrap_or(params.native_app_id),
+                    _ => panic!("Unexpected response to GetCurrency"),
+                }
+            }
+        }
+    }
+
+    /// Hand a stake held in the escrow of this chain to a game, and account it to the game
+    async fn accept_stake(
+        &mut self,
+        game: &GameInfo,
+        owner: AccountOwner,
+        amount: Amount,
+        source_chain_id: Option<String>,
+        selection: Option<String>,
+    ) -> ExtendedResponse {
+        let response = match game.kind {
+            GameKind::PredictionRounds => {
+                let prediction = parse_prediction(selection.as_deref());
+                let rounds_response: rounds::RoundsResponse = self.runtime.call_application(
                     true,
-                    rounds_app_id,
+                    game.app_id.with_abi::<rounds::RoundsAbi>(),
                     &rounds::RoundsOperation::PlaceBet {
-                        owner: source_owner,
+                        owner,
                         amount,
                         prediction: to_rounds_prediction(prediction),
-                        source_chain_id: Some(source_chain_id), // Use SENDER'S chain from message!
+                        source_chain_id,
+                        expected_round_id: None,
+                        min_payout_multiplier: None,
+                        deadline: None,
                     },
                 );
+                match rounds_response {
+                    rounds::RoundsResponse::BetReceipt(receipt) => ExtendedResponse::BetReceipt(from_rounds_receipt(receipt)),
+                    _ => ExtendedResponse::Ok,
+                }
             }
+            GameKind::Lottery => {
+                let lottery_app_id = game.app_id.with_abi::<LotteryRoundsAbi>();
+                let ticket_price = match self.runtime.call_application(true, lottery_app_id, &LotteryRoundsOperation::GetActiveRound) {
+                    LotteryRoundsResponse::LotteryRound(Some(round)) => round.ticket_price,
+                    _ => panic!("No active lottery round"),
+                };
+                let _response: LotteryRoundsResponse = self.runtime.call_application(
+                    true,
+                    lottery_app_id,
+                    &LotteryRoundsOperation::PurchaseTickets {
+                        owner,
+                        amount,
+                        ticket_price,
+                        source_chain_id,
+                    },
+                );
+                ExtendedResponse::Ok
+            }
+            GameKind::Generic => {
+                let _response: GameResponse = self.runtime.call_application(
+                    true,
+                    game.app_id.with_abi::<GameAbi>(),
+                    &GameOperation::AcceptStake {
+                        owner,
+                        amount,
+                        source_chain_id,
+                        selection,
+                    },
+                );
+                ExtendedResponse::Ok
+            }
+        };
+
+        self.state.credit_game(game.app_id, game.currency_app_id, amount).await
+            .expect("Failed to record game escrow");
+        response
+    }
+
+    /// Register an auto-bet strategy whose budget is in the escrow of this chain
+    async fn register_strategy(&mut self, owner: AccountOwner, source_chain_id: Option<String>, config: AutoBetConfig) -> u64 {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let budget = config.budget;
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::RegisterStrategy {
+                owner,
+                config: to_rounds_config(config),
+                source_chain_id,
+            },
+        );
+        self.credit_rounds_stake(rounds_app_id, budget).await;
+        match response {
+            rounds::RoundsResponse::StrategyId(strategy_id) => strategy_id,
+            _ => panic!("Unexpected response to RegisterStrategy"),
         }
     }
 
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
+    /// Cancel an auto-bet strategy in Rounds and refund its unspent budget from the escrow
+    async fn cancel_strategy(&mut self, owner: AccountOwner, strategy_id: u64) {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::CancelStrategy { owner, strategy_id },
+        );
+        let rounds::RoundsResponse::StrategyRefund { owner, amount, source_chain_id } = response else {
+            panic!("Unexpected response to CancelStrategy");
+        };
+        self.refund_rounds_budget(rounds_app_id, owner, amount, source_chain_id, "strategy:refund").await;
+    }
+
+    /// Subscribe `follower` to the bets of `leader` with a budget that is in the escrow of this chain
+    /// A rejected subscription is not accounted to Rounds, so its budget can be returned with `refund_bet`
+    async fn follow(
+        &mut self,
+        follower: AccountOwner,
+        source_chain_id: Option<String>,
+        leader: AccountOwner,
+        ratio_bps: u64,
+        max_per_bet: Amount,
+        budget: Amount,
+    ) -> Result<u64, String> {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::Follow {
+                follower,
+                leader,
+                ratio_bps,
+                max_per_bet,
+                budget,
+                source_chain_id,
+            },
+        );
+        match response {
+            rounds::RoundsResponse::FollowId(follow_id) => {
+                self.credit_rounds_stake(rounds_app_id, budget).await;
+                Ok(follow_id)
+            }
+            rounds::RoundsResponse::FollowRejected(reason) => Err(reason),
+            _ => panic!("Unexpected response to Follow"),
+        }
+    }
+
+    /// Stop a follow subscription in Rounds and refund its unspent budget from the escrow
+    async fn unfollow(&mut self, follower: AccountOwner, follow_id: u64) {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::Unfollow { follower, follow_id },
+        );
+        let rounds::RoundsResponse::FollowRefund { follower, amount, source_chain_id } = response else {
+            panic!("Unexpected response to Unfollow");
+        };
+        self.refund_rounds_budget(rounds_app_id, follower, amount, source_chain_id, "follow:refund").await;
+    }
+
+    /// Place a parlay whose stake is in the escrow of this chain
+    /// A rejected parlay is not accounted to Rounds, so its stake can be returned with `refund_bet`
+    async fn place_parlay(
+        &mut self,
+        owner: AccountOwner,
+        source_chain_id: Option<String>,
+        amount: Amount,
+        predictions: Vec<Prediction>,
+    ) -> Result<u64, String> {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::PlaceParlay {
+                owner,
+                amount,
+                predictions: predictions.into_iter().map(to_rounds_prediction).collect(),
+                source_chain_id,
+            },
+        );
+        match response {
+            rounds::RoundsResponse::ParlayId(parlay_id) => {
+                self.credit_rounds_stake(rounds_app_id, amount).await;
+                Ok(parlay_id)
+            }
+            rounds::RoundsResponse::ParlayRejected(reason) => Err(reason),
+            _ => panic!("Unexpected response to PlaceParlay"),
+        }
+    }
+
+    /// Add funds in the escrow of this chain to the parlay pool of Rounds
+    async fn fund_parlay_pool(&mut self, amount: Amount) {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let _response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::FundParlayPool { amount },
+        );
+        self.credit_rounds_stake(rounds_app_id, amount).await;
+    }
+
+    /// Pay an unspent budget handed back by Rounds out of the escrow of this chain
+    async fn refund_rounds_budget(
+        &mut self,
+        rounds_app_id: ApplicationId<rounds::RoundsAbi>,
+        owner: AccountOwner,
+        amount: Amount,
+        source_chain_id: Option<String>,
+        memo: &str,
+    ) {
+        if amount.is_zero() {
+            return;
+        }
+
+        let currency = self.market_currency(rounds_app_id).forget_abi();
+        self.state.debit_game(rounds_app_id.forget_abi(), currency, amount).await
+            .unwrap_or_else(|e| panic!("Failed to refund budget: {}", e));
+
+        let target_chain = self.reward_target_chain(&source_chain_id);
+        let escrow = self.escrow_owner();
+        self.pay_out(Some(currency), escrow, vec![(owner, amount, target_chain)], Some(memo.to_string()));
+    }
+
+    /// Account a stake placed or queued in the configured Rounds app
+    async fn credit_rounds_stake(&mut self, rounds_app_id: ApplicationId<rounds::RoundsAbi>, amount: Amount) {
+        let currency = self.market_currency(rounds_app_id).forget_abi();
+        self.state.credit_game(rounds_app_id.forget_abi(), currency, amount).await
+            .expect("Failed to record game escrow");
+    }
+
+    /// Chain a reward is paid to: the bettor's chain, or this chain for local bets
+    fn reward_target_chain(&mut self, source_chain_id: &Option<String>) -> ChainId {
+        if let Some(source_chain_id_str) = source_chain_id {
+            source_chain_id_str.parse::<ChainId>().unwrap_or_else(|_| self.runtime.chain_id())
+        } else {
+            self.runtime.chain_id()
+        }
+    }
+
+    /// Record a paid reward in the winner's portfolio (locally or on the winner's chain)
+    async fn notify_reward(&mut self, recipient: AccountOwner, amount: Amount, round_id: Option<u64>, target_chain: ChainId) {
+        if target_chain == self.runtime.chain_id() {
+            let reward = RewardRecord {
+                round_id,
+                amount,
+                received_at: self.runtime.system_time().micros(),
+            };
+            self.state.add_reward(recipient, reward).await
+                .expect("Failed to record reward");
+        } else {
+            self.runtime
+                .prepare_message(Message::RewardReceived { recipient, amount, round_id })
+                .with_authentication()
+                .send_to(target_chain);
+        }
+    }
+
+    /// Fungible token app the Rounds market takes stakes in
+    fn market_currency(&mut self, rounds_app_id: ApplicationId<rounds::RoundsAbi>) -> ApplicationId<FungibleTokenAbi> {
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::GetCurrency,
+        );
+        match response {
+            rounds::RoundsResponse::Currency(currency) => currency.with_abi::<FungibleTokenAbi>(),
+            _ => panic!("Unexpected response to GetCurrency"),
+        }
+    }
+
+    /// Pay `(recipient, amount, chain)` transfers from `payer` in the given currency
+    /// Native payouts go out as one memo-tagged batch; other tokens use standard fungible transfers
+    fn pay_out(
+        &mut self,
+        currency_app_id: Option<ApplicationId>,
+        payer: AccountOwner,
+        payouts: Vec<(AccountOwner, Amount, ChainId)>,
+        memo: Option<String>,
+    ) {
+        let params = self.runtime.application_parameters();
+        let currency = currency_app_id.unwrap_or(params.native_app_id);
+
+        let transfers: Vec<_> = payouts.into_iter().map(|(recipient, amount, target_chain)| {
+            let target_account = linera_sdk::abis::fungible::Account {
+                chain_id: target_chain,
+                owner: recipient,
+            };
+            (target_account, amount)
+        }).collect();
+
+        if currency == params.native_app_id {
+            let _native_response: native::NativeResponse = self.runtime.call_application(
+                true,
+                params.native_app_id.with_abi::<native::NativeAbi>(),
+                &native::NativeOperation::BatchTransfer {
+                    owner: payer,
+                    transfers,
+                    memo,
+                },
+            );
+        } else {
+            let token_app_id = currency.with_abi::<FungibleTokenAbi>();
+            for (target_account, amount) in transfers {
+                let _token_response: FungibleResponse = self.runtime.call_application(
+                    true,
+                    token_app_id,
+                    &FungibleOperation::Transfer {
+                        owner: payer,
+                        amount,
+                        target_account,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Move `amount` of `owner`'s tokens into the escrow on `target_chain` and bet them on `prediction`
+    /// Same-chain bets are placed immediately; cross-chain bets are placed when the message arrives
+    async fn bet_with_stake(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+        target_chain: ChainId,
+        prediction: Prediction,
+        rollover: bool,
+        conditions: BetConditions,
+    ) -> ExtendedResponse {
+        let BetConditions { expected_round_id, min_payout_multiplier, deadline } = conditions;
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let currency = self.market_currency(rounds_app_id);
+
+        // Step 1: Move the stake into the escrow account of this application on the Rounds chain
+        let escrow_account = linera_sdk::abis::fungible::Account {
+            chain_id: target_chain,
+            owner: self.escrow_owner(),
+        };
+        self.fund_bet_stake(currency, owner, amount, escrow_account).await;
+
+        // Step 2: Place bet in Rounds app
+        if target_chain == self.runtime.chain_id() {
+            // Same chain - no source_chain_id needed
+            // Without an active round the bet is either queued or the whole operation fails
+            // A bet pinned to a specific round is never queued for a later one
+            let operation = if rollover && expected_round_id.is_none() && self.active_round(rounds_app_id).is_none() {
+                rounds::RoundsOperation::QueueBet {
+                    owner,
+                    amount,
+                    prediction: to_rounds_prediction(prediction),
+                    source_chain_id: None,
+                }
+            } else {
+                rounds::RoundsOperation::PlaceBet {
+                    owner, // Sender makes the bet
+                    amount,
+                    prediction: to_rounds_prediction(prediction),
+                    source_chain_id: None,
+                    expected_round_id,
+                    min_payout_multiplier,
+                    deadline,
+                }
+            };
+            let rounds_response: rounds::RoundsResponse = self.runtime.call_application(
+                true,
+                rounds_app_id,
+                &operation,
+            );
+            self.credit_rounds_stake(rounds_app_id, amount).await;
+            if let rounds::RoundsResponse::BetReceipt(receipt) = rounds_response {
+                let receipt = from_rounds_receipt(receipt);
+                self.state.confirm_bet(receipt.clone()).await
+                    .expect("Failed to record bet in portfolio");
+                return ExtendedResponse::BetReceipt(receipt);
+            }
+        } else {
+            // Cross-chain - send message with SENDER'S chain_id
+            let message = Message::TransferWithPrediction {
+                owner: escrow_account.owner,
+                amount,
+                prediction,
+                source_chain_id: self.runtime.chain_id().to_string(), // SENDER'S chain!
+                source_owner: owner,
+                rollover,
+                expected_round_id,
+                min_payout_multiplier,
+                deadline,
+            };
+            self.runtime
+                .prepare_message(message)
+                .with_authentication()
+                .send_to(target_chain);
+            
+            // Track the bet locally until the Rounds chain confirms it
+            let pending_bet = PendingBet {
+                amount,
+                prediction,
+                target_chain_id: target_chain.to_string(),
+                sent_at: self.runtime.system_time().micros(),
+            };
+            self.state.add_pending_bet(owner, pending_bet).await
+                .expect("Failed to record pending bet");
+        }
+
+        ExtendedResponse::Ok
+    }
+
+    /// Record a queued bet that Rounds placed, or tell the bettor's chain it was placed or refunded
+    async fn settle_queued_bet(&mut self, bet: QueuedBetOutcome) {
+        let Some(source_chain_id) = bet.source_chain_id else {
+            // Queued from this chain, which keeps no pending bet for it
+            if let Some(receipt) = bet.receipt {
+                self.state.confirm_bet(receipt).await
+                    .expect("Failed to record bet in portfolio");
+            }
+            return;
+        };
+        let source_chain = source_chain_id.parse::<ChainId>()
+            .unwrap_or_else(|_| self.runtime.chain_id());
+        let message = match bet.receipt {
+            Some(receipt) => Message::BetConfirmed { receipt },
+            None => Message::BetRefunded { owner: bet.owner, amount: bet.amount, prediction: bet.prediction },
+        };
+        self.runtime
+            .prepare_message(message)
+            .with_authentication()
+            .send_to(source_chain);
+    }
+
+    /// Forward an operation to the Native app, keeping the signer's authentication
+    fn call_native(&mut self, operation: native::NativeOperation) -> native::NativeResponse {
+        let params = self.runtime.application_parameters();
+        let native_app_id = params.native_app_id.with_abi::<native::NativeAbi>();
+        self.runtime.call_application(true, native_app_id, &operation)
+    }
+
+    /// Get the Rounds round currently accepting bets, if any
+    fn active_round(&mut self, rounds_app_id: ApplicationId<rounds::RoundsAbi>) -> Option<rounds::PredictionRound> {
+        let response: rounds::RoundsResponse = self.runtime.call_application(
+            true,
+            rounds_app_id,
+            &rounds::RoundsOperation::GetActiveRound,
+        );
+        match response {
+            rounds::RoundsResponse::PredictionRound(Some(round)) if round.status == rounds::RoundStatus::Active => Some(round),
+            _ => None,
+        }
+    }
+
+    /// Return a rejected cross-chain stake, not yet accounted to any game, from the escrow to the bettor's chain
+    fn refund_bet(&mut self, amount: Amount, source_chain_id: &str, source_owner: AccountOwner, memo: &str) {
+        let params = self.runtime.application_parameters();
+        let rounds_app_id = params.rounds_app_id.with_abi::<rounds::RoundsAbi>();
+        let currency = self.market_currency(rounds_app_id).forget_abi();
+
+        let target_chain = source_chain_id.parse::<ChainId>().unwrap_or_else(|_| self.runtime.chain_id());
+        let escrow = self.escrow_owner();
+        self.pay_out(
+            Some(currency),
+            escrow,
+            vec![(source_owner, amount, target_chain)],
+            Some(memo.to_string()),
+        );
     }
 }
\ No newline at end of file
@@ -3,34 +3,180 @@
 
 /*! Microbetreal - Betting Wrapper Application */
 
-use async_graphql::{Request, Response};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi};
+use async_graphql::{Request, Response, SimpleObject};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId, ChainId, ContractAbi, ServiceAbi};
 use serde::{Deserialize, Serialize};
 
 // Re-export from native-fungible-abi
-pub use native_fungible_abi::{Prediction, ExtendedOperation, ExtendedResponse, ExtendedNativeFungibleTokenAbi};
+pub use native_fungible_abi::{
+    Prediction, BetReceipt, ExtendedOperation, ExtendedResponse, ExtendedNativeFungibleTokenAbi,
+    GameKind, GameAbi, GameOperation, GameResponse, StrategyKind, AutoBetConfig, QueuedBetOutcome,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
     // Cross-chain transfer with prediction
     TransferWithPrediction {
+        owner: AccountOwner, // Escrow account holding the stake on the Rounds chain
+        amount: Amount,
+        prediction: Prediction,
+        source_chain_id: String, // Chain ID of the sender
+        source_owner: AccountOwner,
+        rollover: bool, // Queue the bet for the next round instead of refunding it
+        expected_round_id: Option<u64>, // Slippage protection - refund if any condition is not met
+        min_payout_multiplier: Option<u64>,
+        deadline: Option<u64>,
+    },
+    // Sent to the chain holding the tokens of a Claim with prediction, which stakes them from there
+    ClaimWithPrediction {
+        source_owner: AccountOwner,
+        amount: Amount,
+        target_chain_id: ChainId, // Chain of the Rounds app
+        prediction: Prediction,
+    },
+    // Confirmation sent back to the bettor's chain once a cross-chain bet is placed
+    BetConfirmed {
+        receipt: BetReceipt,
+    },
+    // Sent back to the bettor's chain when a cross-chain bet was refunded
+    BetRefunded {
         owner: AccountOwner,
         amount: Amount,
         prediction: Prediction,
+    },
+    // Sent to the winner's chain when a reward is paid out
+    RewardReceived {
+        recipient: AccountOwner,
+        amount: Amount,
+        round_id: Option<u64>,
+    },
+    // Cross-chain auto-bet strategy - the budget is already in the escrow of the Rounds chain
+    RegisterStrategy {
+        config: AutoBetConfig,
+        source_chain_id: String, // Chain ID of the sender
+        source_owner: AccountOwner,
+    },
+    // Cross-chain cancellation of an auto-bet strategy
+    CancelStrategy {
+        strategy_id: u64,
+        source_owner: AccountOwner,
+    },
+    // Cross-chain stake in a registered game - the stake is already in the escrow of the game's chain
+    StakeForGame {
+        game_id: String, // Resolved in the registry of the game's chain
+        amount: Amount,
+        source_chain_id: String, // Chain ID of the sender
+        source_owner: AccountOwner,
+        selection: Option<String>,
+    },
+    // Cross-chain copy-betting subscription - the budget is already in the escrow of the Rounds chain
+    Follow {
+        leader: AccountOwner,
+        ratio_bps: u64,
+        max_per_bet: Amount,
+        budget: Amount,
         source_chain_id: String, // Chain ID of the sender
         source_owner: AccountOwner,
     },
+    // Cross-chain cancellation of a copy-betting subscription
+    Unfollow {
+        follow_id: u64,
+        source_owner: AccountOwner,
+    },
+    // Cross-chain parlay - the stake is already in the escrow of the Rounds chain
+    PlaceParlay {
+        amount: Amount,
+        predictions: Vec<Prediction>,
+        source_chain_id: String, // Chain ID of the sender
+        source_owner: AccountOwner,
+    },
+    // Cross-chain parlay pool funding - the funds are already in the escrow of the Rounds chain
+    FundParlayPool {
+        amount: Amount,
+    },
+}
+
+// A game application in the registry of this chain
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameInfo {
+    pub game_id: String,
+    pub app_id: ApplicationId,
+    pub kind: GameKind,
+    pub currency_app_id: ApplicationId, // Token the game takes stakes in
+    pub registered_at: u64,
+}
+
+// A session key allowed to bet on behalf of an owner within a budget
+#[derive(Debug, Clone, Deserialize, Serialize, SimpleObject)]
+pub struct Session {
+    pub session_key: AccountOwner,
+    pub max_total: Amount,   // Total stake the session may place
+    pub max_per_bet: Amount, // Largest single bet
+    pub spent: Amount,       // Stake placed so far
+    pub expires_at: u64,
+    pub created_at: u64,
+}
+
+impl Session {
+    /// Budget the session can still spend at `now`
+    pub fn remaining(&self, now: u64) -> Amount {
+        if now > self.expires_at {
+            Amount::ZERO
+        } else {
+            self.max_total.saturating_sub(self.spent)
+        }
+    }
+}
+
+// Registry entry as returned by queries
+#[derive(Debug, Clone, SimpleObject)]
+pub struct GameSummary {
+    pub game_id: String,
+    pub app_id: String,
+    pub kind: GameKind,
+    pub currency_app_id: String,
+    pub escrow_balance: Amount, // Stakes of the game held in the escrow of this chain
+}
+
+// A cross-chain bet sent from this chain that has not been confirmed yet
+#[derive(Debug, Clone, Deserialize, Serialize, SimpleObject)]
+pub struct PendingBet {
+    pub amount: Amount,
+    pub prediction: Prediction,
+    pub target_chain_id: String, // Chain where the Rounds app runs
+    pub sent_at: u64,
+}
+
+// A reward received on this chain
+#[derive(Debug, Clone, Deserialize, Serialize, SimpleObject)]
+pub struct RewardRecord {
+    pub round_id: Option<u64>,
+    pub amount: Amount,
+    pub received_at: u64,
+}
+
+// Local record of an owner's betting activity on this chain
+// Only the most recent pending bets, confirmed bets and rewards are kept
+#[derive(Debug, Clone, Default, Deserialize, Serialize, SimpleObject)]
+pub struct Portfolio {
+    pub pending_bets: Vec<PendingBet>,
+    pub confirmed_bets: Vec<BetReceipt>,
+    pub rewards: Vec<RewardRecord>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MicrobetParameters {
     pub native_app_id: ::linera_sdk::linera_base_types::ApplicationId,
     pub rounds_app_id: ::linera_sdk::linera_base_types::ApplicationId,
+    /// Owner allowed to manage the game registry (None = the registry cannot be changed)
+    #[serde(default)]
+    pub admin: Option<AccountOwner>,
 }
 
 // Microbetreal implements the same ABI as NativeFungible (ExtendedNativeFungibleTokenAbi)
 // This allows Rounds to call operations on Microbetreal using the shared ABI
-// Microbetreal handles: TransferWithPrediction, SendReward, SetNativeAppId, SetRoundsAppId
+// Microbetreal handles: Transfer and Claim with prediction, SendReward(s), SetNativeAppId, SetRoundsAppId
+// and routes stakes to registered games (RegisterGame, UnregisterGame, Play)
 // Other operations are passed through to Native app
 
 pub struct MicrobetAbi;
@@ -7,10 +7,13 @@ use std::sync::Arc;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    linera_base_types::{AccountOwner, Amount, ApplicationId, WithServiceAbi},
     Service, ServiceRuntime,
 };
-use microbetreal::{MicrobetAbi, ExtendedOperation, Prediction};
+use microbetreal::{
+    MicrobetAbi, AutoBetConfig, BetReceipt, ExtendedOperation, GameInfo, GameKind, GameSummary, Portfolio, Prediction,
+    Session, StrategyKind,
+};
 use native::AccountInput;
 
 linera_sdk::service!(MicrobetService);
@@ -77,6 +80,46 @@ impl QueryRoot {
         Some(format!("{}", params.rounds_app_id))
     }
     
+    /// Get the betting portfolio of an owner: pending bets, and the most recent confirmed bets and rewards
+    async fn portfolio(&self, owner: AccountOwner) -> Portfolio {
+        self.state.get_portfolio(&owner).await.unwrap_or_default()
+    }
+    
+    /// Get the latest bet confirmation for an owner ("bet confirmed in round N")
+    async fn last_bet_confirmation(&self, owner: AccountOwner) -> Option<BetReceipt> {
+        self.state.get_portfolio(&owner).await.ok()
+            .and_then(|portfolio| portfolio.confirmed_bets.last().cloned())
+    }
+    
+    /// Get the session keys an owner authorized, with their limits and spending
+    async fn sessions(&self, owner: AccountOwner) -> Vec<Session> {
+        self.state.get_sessions(&owner).await.unwrap_or_default()
+    }
+
+    /// Get the games registered on this chain with the stakes held for them
+    async fn games(&self) -> Vec<GameSummary> {
+        let mut summaries = Vec::new();
+        for game in self.state.get_games().await.unwrap_or_default() {
+            summaries.push(self.game_summary(game).await);
+        }
+        summaries
+    }
+
+    /// Get a registered game by id
+    async fn game(&self, game_id: String) -> Option<GameSummary> {
+        match self.state.get_game(&game_id).await.ok().flatten() {
+            Some(game) => Some(self.game_summary(game).await),
+            None => None,
+        }
+    }
+
+    /// Get the stakes held in the escrow of this chain for a game application in a currency
+    async fn game_escrow(&self, game_app_id: String, currency_app_id: String) -> Option<Amount> {
+        let game_app_id = game_app_id.parse::<ApplicationId>().ok()?;
+        let currency_app_id = currency_app_id.parse::<ApplicationId>().ok()?;
+        self.state.get_game_escrow(game_app_id, currency_app_id).await.ok()
+    }
+
     /// Check if app IDs are configured (always true with parameters)
     async fn is_configured(&self) -> bool {
         true
@@ -88,6 +131,20 @@ impl QueryRoot {
     }
 }
 
+impl QueryRoot {
+    async fn game_summary(&self, game: GameInfo) -> GameSummary {
+        let escrow_balance = self.state.get_game_escrow(game.app_id, game.currency_app_id).await
+            .unwrap_or_default();
+        GameSummary {
+            game_id: game.game_id,
+            app_id: game.app_id.to_string(),
+            kind: game.kind,
+            currency_app_id: game.currency_app_id.to_string(),
+            escrow_balance,
+        }
+    }
+}
+
 struct MutationRoot {
     runtime: Arc<ServiceRuntime<MicrobetService>>,
 }
@@ -107,13 +164,18 @@ impl MutationRoot {
     }
 
     /// Transfer tokens with prediction (betting)
-    /// Optionally set app IDs on-the-fly
+    /// Set `rollover` to queue the bet for the next round instead of refunding it
+    /// when no round is accepting bets
     async fn transfer_with_prediction(
         &self,
         owner: AccountOwner,
         amount: String,
         target_account: AccountInput,
         prediction: Prediction,
+        rollover: Option<bool>,
+        expected_round_id: Option<u64>,
+        min_payout_multiplier: Option<u64>,
+        deadline: Option<u64>,
     ) -> String {
         let fungible_account = linera_sdk::abis::fungible::Account {
             chain_id: target_account.chain_id,
@@ -126,8 +188,231 @@ impl MutationRoot {
             amount: amount.parse::<Amount>().unwrap_or_default(),
             target_account: fungible_account,
             prediction: Some(prediction),
+            rollover: rollover.unwrap_or(false),
+            expected_round_id,
+            min_payout_multiplier,
+            deadline,
         });
         
         "TransferWithPrediction operation scheduled - bet will be placed".to_string()
     }
+
+    /// Authorize a session key to bet for `owner` within a total budget, a per-bet limit and an expiry (micros)
+    async fn create_session(
+        &self,
+        owner: AccountOwner,
+        session_key: AccountOwner,
+        max_total: String,
+        max_per_bet: String,
+        expires_at: u64,
+    ) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::CreateSession {
+            owner,
+            session_key,
+            max_total: max_total.parse::<Amount>().unwrap_or_default(),
+            max_per_bet: max_per_bet.parse::<Amount>().unwrap_or_default(),
+            expires_at,
+        });
+        "CreateSession operation scheduled".to_string()
+    }
+
+    /// Revoke a session key and release the rest of its budget
+    async fn revoke_session(&self, owner: AccountOwner, session_key: AccountOwner) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::RevokeSession { owner, session_key });
+        "RevokeSession operation scheduled".to_string()
+    }
+
+    /// Fund an auto-bet strategy that bets in every new round of the Rounds app on the chain of `target_account`
+    async fn create_strategy(
+        &self,
+        owner: AccountOwner,
+        target_account: AccountInput,
+        kind: StrategyKind,
+        prediction: Prediction,
+        base_amount: String,
+        max_amount: Option<String>,
+        budget: String,
+        loss_limit: Option<String>,
+    ) -> String {
+        let base_amount = base_amount.parse::<Amount>().unwrap_or_default();
+        let config = AutoBetConfig {
+            kind,
+            prediction,
+            base_amount,
+            max_amount: max_amount.and_then(|amount| amount.parse::<Amount>().ok()).unwrap_or(base_amount),
+            budget: budget.parse::<Amount>().unwrap_or_default(),
+            loss_limit: loss_limit.and_then(|amount| amount.parse::<Amount>().ok()),
+        };
+        self.runtime.schedule_operation(&ExtendedOperation::CreateStrategy {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            config,
+        });
+        "CreateStrategy operation scheduled".to_string()
+    }
+
+    /// Stop an auto-bet strategy and refund its unspent budget
+    async fn cancel_strategy(&self, owner: AccountOwner, target_account: AccountInput, strategy_id: u64) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::CancelStrategy {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            strategy_id,
+        });
+        "CancelStrategy operation scheduled".to_string()
+    }
+
+    /// Copy the bets of `leader` with a budget; each copy stakes `ratio_bps` of the leader's amount
+    async fn follow(
+        &self,
+        owner: AccountOwner,
+        target_account: AccountInput,
+        leader: AccountOwner,
+        ratio_bps: u64,
+        max_per_bet: String,
+        budget: String,
+    ) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Follow {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            leader,
+            ratio_bps,
+            max_per_bet: max_per_bet.parse::<Amount>().unwrap_or_default(),
+            budget: budget.parse::<Amount>().unwrap_or_default(),
+        });
+        "Follow operation scheduled".to_string()
+    }
+
+    /// Stop copying a leader and refund the unspent budget
+    async fn unfollow(&self, owner: AccountOwner, target_account: AccountInput, follow_id: u64) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Unfollow {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            follow_id,
+        });
+        "Unfollow operation scheduled".to_string()
+    }
+
+    /// Predict the next rounds, one prediction per round starting with the active one, with a single stake
+    async fn place_parlay(&self, owner: AccountOwner, target_account: AccountInput, amount: String, predictions: Vec<Prediction>) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::PlaceParlay {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+            predictions,
+        });
+        "PlaceParlay operation scheduled".to_string()
+    }
+
+    /// Add funds to the pool parlay winnings are paid from
+    async fn fund_parlay_pool(&self, owner: AccountOwner, target_account: AccountInput, amount: String) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::FundParlayPool {
+            owner,
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+        });
+        "FundParlayPool operation scheduled".to_string()
+    }
+
+    /// Register a game application in the registry of this chain
+    async fn register_game(&self, game_id: String, app_id: String, kind: GameKind) -> String {
+        match app_id.parse::<ApplicationId>() {
+            Ok(app_id) => {
+                self.runtime.schedule_operation(&ExtendedOperation::RegisterGame { game_id: game_id.clone(), app_id, kind });
+                format!("RegisterGame operation scheduled for {}", game_id)
+            }
+            Err(e) => format!("Invalid application ID: {:?}", e),
+        }
+    }
+
+    /// Remove a game from the registry of this chain
+    async fn unregister_game(&self, game_id: String) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::UnregisterGame { game_id: game_id.clone() });
+        format!("UnregisterGame operation scheduled for {}", game_id)
+    }
+
+    /// Account stakes the Rounds app took before escrow accounting existed (registry admin only, once)
+    async fn credit_legacy_rounds_escrow(&self, amount: String) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::CreditLegacyRoundsEscrow {
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+        });
+        "CreditLegacyRoundsEscrow operation scheduled".to_string()
+    }
+
+    /// Stake tokens in a registered game running on the chain of `target_account`
+    async fn play(
+        &self,
+        owner: AccountOwner,
+        amount: String,
+        target_account: AccountInput,
+        game_id: String,
+        selection: Option<String>,
+    ) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Play {
+            owner,
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            game_id: game_id.clone(),
+            selection,
+        });
+        format!("Play operation scheduled for {}", game_id)
+    }
+
+    /// Claim tokens from another chain, optionally betting them on a prediction
+    async fn claim(
+        &self,
+        source_account: AccountInput,
+        amount: String,
+        target_account: AccountInput,
+        prediction: Option<Prediction>,
+    ) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Claim {
+            source_account: linera_sdk::abis::fungible::Account {
+                chain_id: source_account.chain_id,
+                owner: source_account.owner,
+            },
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+            target_account: linera_sdk::abis::fungible::Account {
+                chain_id: target_account.chain_id,
+                owner: target_account.owner,
+            },
+            prediction,
+        });
+        "Claim operation scheduled".to_string()
+    }
+
+    /// Withdraw all tokens of the signer to the chain account
+    async fn withdraw(&self) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Withdraw);
+        "Withdraw operation scheduled".to_string()
+    }
+
+    /// Mint new tokens (restricted to Native's configured minters)
+    async fn mint(&self, owner: AccountOwner, amount: String) -> String {
+        self.runtime.schedule_operation(&ExtendedOperation::Mint {
+            owner,
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+        });
+        "Mint operation scheduled".to_string()
+    }
 }
\ No newline at end of file
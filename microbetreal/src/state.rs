@@ -1,10 +1,11 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
-use linera_sdk::linera_base_types::ApplicationId;
+use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext, ViewError};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId};
+use microbetreal::{BetReceipt, GameInfo, PendingBet, Portfolio, Prediction, RewardRecord, Session};
 
-/// Minimal state for Microbetreal - just stores app IDs for coordination
+/// State for Microbetreal - app IDs for coordination and per-owner portfolios
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct MicrobetState {
@@ -12,4 +13,219 @@ pub struct MicrobetState {
     pub native_app_id: RegisterView<Option<ApplicationId<native::NativeAbi>>>,
     /// ApplicationId of the Rounds game app
     pub rounds_app_id: RegisterView<Option<ApplicationId<rounds::RoundsAbi>>>,
+    /// Betting activity of each owner on this chain
+    pub portfolios: MapView<AccountOwner, Portfolio>,
+    /// Game applications that stakes can be routed to, by game id
+    pub games: MapView<String, GameInfo>,
+    /// Stakes held in the escrow of this chain per (game application, currency)
+    pub game_escrow: MapView<(ApplicationId, ApplicationId), Amount>,
+    /// Session keys authorized by each owner
+    pub sessions: MapView<AccountOwner, Vec<Session>>,
+    /// Whether the untracked Rounds stakes from before escrow accounting were credited
+    pub legacy_rounds_escrow_credited: RegisterView<bool>,
+}
+
+/// Number of confirmed bets and rewards kept in a portfolio, older entries are pruned
+const MAX_PORTFOLIO_HISTORY: usize = 50;
+
+#[allow(dead_code)]
+impl MicrobetState {
+    /// Get the portfolio of an owner (empty if the owner never bet from this chain)
+    pub async fn get_portfolio(&self, owner: &AccountOwner) -> Result<Portfolio, String> {
+        Ok(self.portfolios.get(owner).await
+            .map_err(|e: ViewError| format!("Failed to get portfolio: {:?}", e))?
+            .unwrap_or_default())
+    }
+
+    /// Record a cross-chain bet that is waiting for confirmation, dropping the oldest beyond `MAX_PORTFOLIO_HISTORY`
+    pub async fn add_pending_bet(&mut self, owner: AccountOwner, bet: PendingBet) -> Result<(), String> {
+        let mut portfolio = self.get_portfolio(&owner).await?;
+        push_capped(&mut portfolio.pending_bets, bet);
+        self.portfolios.insert(&owner, portfolio)
+            .map_err(|e: ViewError| format!("Failed to store portfolio: {:?}", e))
+    }
+
+    /// Record a confirmed bet, settling the oldest matching pending bet
+    pub async fn confirm_bet(&mut self, receipt: BetReceipt) -> Result<(), String> {
+        let mut portfolio = self.get_portfolio(&receipt.owner).await?;
+        remove_pending(&mut portfolio, receipt.amount, receipt.prediction);
+        let owner = receipt.owner;
+        push_capped(&mut portfolio.confirmed_bets, receipt);
+        self.portfolios.insert(&owner, portfolio)
+            .map_err(|e: ViewError| format!("Failed to store portfolio: {:?}", e))
+    }
+
+    /// Drop a pending bet whose stake was refunded
+    pub async fn refund_bet(&mut self, owner: AccountOwner, amount: Amount, prediction: Prediction) -> Result<(), String> {
+        let mut portfolio = self.get_portfolio(&owner).await?;
+        remove_pending(&mut portfolio, amount, prediction);
+        self.portfolios.insert(&owner, portfolio)
+            .map_err(|e: ViewError| format!("Failed to store portfolio: {:?}", e))
+    }
+
+    /// Get a registered game
+    pub async fn get_game(&self, game_id: &str) -> Result<Option<GameInfo>, String> {
+        self.games.get(game_id).await
+            .map_err(|e: ViewError| format!("Failed to get game: {:?}", e))
+    }
+
+    /// Get all registered games
+    pub async fn get_games(&self) -> Result<Vec<GameInfo>, String> {
+        let game_ids = self.games.indices().await
+            .map_err(|e: ViewError| format!("Failed to get game ids: {:?}", e))?;
+        let mut games = Vec::new();
+        for game_id in game_ids {
+            if let Some(game) = self.get_game(&game_id).await? {
+                games.push(game);
+            }
+        }
+        Ok(games)
+    }
+
+    /// Add a game to the registry (fails if the game id is taken)
+    pub async fn register_game(&mut self, game: GameInfo) -> Result<(), String> {
+        if self.get_game(&game.game_id).await?.is_some() {
+            return Err(format!("Game {} is already registered", game.game_id));
+        }
+        let game_id = game.game_id.clone();
+        self.games.insert(&game_id, game)
+            .map_err(|e: ViewError| format!("Failed to store game: {:?}", e))
+    }
+
+    /// Remove a game from the registry - stakes already held for it stay payable
+    pub async fn unregister_game(&mut self, game_id: &str) -> Result<(), String> {
+        if self.get_game(game_id).await?.is_none() {
+            return Err(format!("Game {} is not registered", game_id));
+        }
+        self.games.remove(game_id)
+            .map_err(|e: ViewError| format!("Failed to remove game: {:?}", e))
+    }
+
+    /// Stakes held in the escrow for a game in a currency
+    pub async fn get_game_escrow(&self, game_app_id: ApplicationId, currency_app_id: ApplicationId) -> Result<Amount, String> {
+        Ok(self.game_escrow.get(&(game_app_id, currency_app_id)).await
+            .map_err(|e: ViewError| format!("Failed to get game escrow: {:?}", e))?
+            .unwrap_or_default())
+    }
+
+    /// Stakes held in the escrow for all games in a currency
+    pub async fn total_game_escrow(&self, currency_app_id: ApplicationId) -> Result<Amount, String> {
+        let keys = self.game_escrow.indices().await
+            .map_err(|e: ViewError| format!("Failed to get game escrow keys: {:?}", e))?;
+        let mut total = Amount::ZERO;
+        for (game_app_id, currency) in keys {
+            if currency == currency_app_id {
+                total = total.saturating_add(self.get_game_escrow(game_app_id, currency).await?);
+            }
+        }
+        Ok(total)
+    }
+
+    /// Record a stake accepted by a game
+    pub async fn credit_game(&mut self, game_app_id: ApplicationId, currency_app_id: ApplicationId, amount: Amount) -> Result<(), String> {
+        let balance = self.get_game_escrow(game_app_id, currency_app_id).await?.saturating_add(amount);
+        self.game_escrow.insert(&(game_app_id, currency_app_id), balance)
+            .map_err(|e: ViewError| format!("Failed to store game escrow: {:?}", e))
+    }
+
+    /// Record a payout made by a game (fails if it exceeds what was staked in the game)
+    pub async fn debit_game(&mut self, game_app_id: ApplicationId, currency_app_id: ApplicationId, amount: Amount) -> Result<(), String> {
+        let balance = self.get_game_escrow(game_app_id, currency_app_id).await?;
+        let balance = balance.try_sub(amount)
+            .map_err(|_| format!("Payout of {} exceeds the {} held for the game", amount, balance))?;
+        self.game_escrow.insert(&(game_app_id, currency_app_id), balance)
+            .map_err(|e: ViewError| format!("Failed to store game escrow: {:?}", e))
+    }
+
+    /// Get the sessions of an owner
+    pub async fn get_sessions(&self, owner: &AccountOwner) -> Result<Vec<Session>, String> {
+        Ok(self.sessions.get(owner).await
+            .map_err(|e: ViewError| format!("Failed to get sessions: {:?}", e))?
+            .unwrap_or_default())
+    }
+
+    /// Add a session, replacing any previous session of the same key
+    /// Expired sessions of the owner are dropped
+    pub async fn add_session(&mut self, owner: AccountOwner, session: Session, now: u64) -> Result<(), String> {
+        if session.max_per_bet > session.max_total {
+            return Err("Max per bet exceeds the session budget".to_string());
+        }
+        if session.expires_at <= now {
+            return Err("Session expiry is in the past".to_string());
+        }
+        let mut sessions = self.get_sessions(&owner).await?;
+        sessions.retain(|existing| existing.session_key != session.session_key && existing.expires_at >= now);
+        sessions.push(session);
+        self.sessions.insert(&owner, sessions)
+            .map_err(|e: ViewError| format!("Failed to store sessions: {:?}", e))
+    }
+
+    /// Remove a session key of an owner
+    pub async fn revoke_session(&mut self, owner: AccountOwner, session_key: AccountOwner) -> Result<(), String> {
+        let mut sessions = self.get_sessions(&owner).await?;
+        let count = sessions.len();
+        sessions.retain(|session| session.session_key != session_key);
+        if sessions.len() == count {
+            return Err("No such session".to_string());
+        }
+        self.sessions.insert(&owner, sessions)
+            .map_err(|e: ViewError| format!("Failed to store sessions: {:?}", e))
+    }
+
+    /// Whether `session_key` is (or was) a session key of `owner`
+    pub async fn is_session_key(&self, owner: &AccountOwner, session_key: &AccountOwner) -> Result<bool, String> {
+        Ok(self.get_sessions(owner).await?.iter().any(|session| &session.session_key == session_key))
+    }
+
+    /// Charge a bet to a session, enforcing its expiry and limits
+    pub async fn spend_session(&mut self, owner: AccountOwner, session_key: AccountOwner, amount: Amount, now: u64) -> Result<(), String> {
+        let mut sessions = self.get_sessions(&owner).await?;
+        let session = sessions.iter_mut()
+            .find(|session| session.session_key == session_key)
+            .ok_or_else(|| "No such session".to_string())?;
+        if now > session.expires_at {
+            return Err("Session has expired".to_string());
+        }
+        if amount > session.max_per_bet {
+            return Err(format!("Bet of {} exceeds the per-bet limit of {}", amount, session.max_per_bet));
+        }
+        if amount > session.remaining(now) {
+            return Err(format!("Bet of {} exceeds the remaining session budget of {}", amount, session.remaining(now)));
+        }
+        session.spent = session.spent.saturating_add(amount);
+        self.sessions.insert(&owner, sessions)
+            .map_err(|e: ViewError| format!("Failed to store sessions: {:?}", e))
+    }
+
+    /// Total budget the active sessions of an owner can still spend
+    pub async fn session_budget(&self, owner: &AccountOwner, now: u64) -> Result<Amount, String> {
+        Ok(self.get_sessions(owner).await?.iter()
+            .fold(Amount::ZERO, |total, session| total.saturating_add(session.remaining(now))))
+    }
+
+    /// Record a reward received by an owner
+    pub async fn add_reward(&mut self, owner: AccountOwner, reward: RewardRecord) -> Result<(), String> {
+        let mut portfolio = self.get_portfolio(&owner).await?;
+        push_capped(&mut portfolio.rewards, reward);
+        self.portfolios.insert(&owner, portfolio)
+            .map_err(|e: ViewError| format!("Failed to store portfolio: {:?}", e))
+    }
+}
+
+/// Append an entry, dropping the oldest ones beyond `MAX_PORTFOLIO_HISTORY`
+fn push_capped<T>(entries: &mut Vec<T>, entry: T) {
+    entries.push(entry);
+    if entries.len() > MAX_PORTFOLIO_HISTORY {
+        entries.drain(..entries.len() - MAX_PORTFOLIO_HISTORY);
+    }
+}
+
+/// Remove the oldest pending bet with the given amount and side
+/// Messages between two chains are delivered in order, so the oldest match is the one being settled
+fn remove_pending(portfolio: &mut Portfolio, amount: Amount, prediction: Prediction) {
+    if let Some(index) = portfolio.pending_bets.iter()
+        .position(|bet| bet.amount == amount && bet.prediction == prediction)
+    {
+        portfolio.pending_bets.remove(index);
+    }
 }
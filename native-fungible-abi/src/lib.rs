@@ -4,7 +4,7 @@
 /*! ABI definitions for Native Fungible Token Application */
 
 use async_graphql::{Request, Response, SimpleObject, InputObject};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi, ChainId};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi, ChainId};
 use serde::{Deserialize, Serialize};
 
 pub const TICKER_SYMBOL: &str = "NAT";
@@ -16,6 +16,63 @@ pub enum Prediction {
     Down,
 }
 
+// Receipt for a bet accepted into a prediction round
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct BetReceipt {
+    pub round_id: u64,
+    pub owner: AccountOwner,
+    pub prediction: Prediction,
+    pub amount: Amount,       // Amount of this bet
+    pub total_stake: Amount,  // Cumulative stake of the owner on this side of the round
+    pub sequence: u64,        // Bet sequence number
+}
+
+// Kind of game application routed by Microbetreal - decides how stakes are handed to the game
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, async_graphql::Enum)]
+pub enum GameKind {
+    PredictionRounds, // Rounds app - the selection is the prediction ("Up" / "Down")
+    Lottery,          // Lottery rounds app - the stake buys tickets
+    Generic,          // Any application implementing `GameAbi`
+}
+
+// Rule an auto-bet strategy uses to pick each bet (mirrors `rounds::StrategyKind`)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, async_graphql::Enum)]
+pub enum StrategyKind {
+    FixedSide,        // Always bet `prediction`
+    Alternate,        // Start with `prediction`, then switch sides every round
+    FollowLastResult, // Bet on the result of the last resolved round
+    Martingale,       // Double the stake after each loss, up to `max_amount`
+}
+
+// Standing instruction for Rounds to bet in every new round, funded by `budget`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AutoBetConfig {
+    pub kind: StrategyKind,
+    pub prediction: Prediction,
+    pub base_amount: Amount,
+    pub max_amount: Amount,         // Largest stake (Martingale)
+    pub budget: Amount,             // Moved into the escrow when the strategy is created
+    pub loss_limit: Option<Amount>, // Stop once net losses reach this
+}
+
+// One winner's payout in a batched reward distribution
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RewardPayout {
+    pub recipient: AccountOwner,
+    pub amount: Amount,
+    pub source_chain_id: Option<String>,
+}
+
+// A queued bet that Rounds placed (with its receipt) or refunded (without one) when a round opened
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueuedBetOutcome {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub prediction: Prediction,
+    pub source_chain_id: Option<String>,
+    pub receipt: Option<BetReceipt>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
     Notify,
@@ -58,11 +115,23 @@ pub enum ExtendedOperation {
     /// Get the ticker symbol
     TickerSymbol,
     /// Transfer tokens between accounts with optional prediction
+    /// If `rollover` is set, a bet that arrives while no round is active is queued
+    /// for the next round instead of being refunded
+    /// `expected_round_id`, `min_payout_multiplier` (basis points, 10_000 = 1.0x) and `deadline` (micros)
+    /// protect the bet against slippage: if any of them is not met the bet is rejected and the stake refunded
     Transfer {
         owner: AccountOwner,
         amount: Amount,
         target_account: linera_sdk::abis::fungible::Account,
         prediction: Option<Prediction>,
+        #[serde(default)]
+        rollover: bool,
+        #[serde(default)]
+        expected_round_id: Option<u64>,
+        #[serde(default)]
+        min_payout_multiplier: Option<u64>,
+        #[serde(default)]
+        deadline: Option<u64>,
     },
     /// Claim tokens from another chain
     Claim {
@@ -91,6 +160,104 @@ pub enum ExtendedOperation {
         recipient: AccountOwner,
         amount: Amount,
         source_chain_id: Option<String>,
+        #[serde(default)]
+        round_id: Option<u64>, // Round the reward was won in
+        #[serde(default)]
+        memo: Option<String>, // Reference recorded on the Native transfer, e.g. `round:123:payout`
+        #[serde(default)]
+        currency_app_id: Option<ApplicationId>, // Token the reward is paid in (None = Native)
+    },
+    /// Send rewards to all winners of a round in one Native batch transfer (internal - called by Rounds app)
+    SendRewards {
+        rewards: Vec<RewardPayout>,
+        round_id: Option<u64>,
+        memo: Option<String>,
+        currency_app_id: Option<ApplicationId>, // Token the rewards are paid in (None = Native)
+    },
+
+    // Game registry (for Microbetreal)
+    /// Register a game application under `game_id` on this chain
+    RegisterGame {
+        game_id: String,
+        app_id: ApplicationId,
+        kind: GameKind,
+    },
+    /// Remove a game from the registry of this chain
+    UnregisterGame { game_id: String },
+    /// Account stakes the Rounds app took before escrow accounting existed (registry admin only, once per chain)
+    /// `amount` cannot exceed the escrow tokens that are not already assigned to a game
+    CreditLegacyRoundsEscrow { amount: Amount },
+    /// Authorize `session_key` to bet on behalf of `owner` within the given limits until `expires_at` (micros)
+    /// The remaining budget of the owner's sessions is approved to Microbetreal (in the Rounds market currency),
+    /// which funds session bets from that allowance
+    /// Sessions only place Rounds bets (Transfer with a prediction); other stakes must be signed by the owner
+    CreateSession {
+        owner: AccountOwner,
+        session_key: AccountOwner,
+        max_total: Amount,
+        max_per_bet: Amount,
+        expires_at: u64,
+    },
+    /// Revoke a session key of `owner` and release the rest of its budget
+    RevokeSession {
+        owner: AccountOwner,
+        session_key: AccountOwner,
+    },
+    /// Fund an auto-bet strategy in the Rounds app running on the chain of `target_account`
+    CreateStrategy {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        config: AutoBetConfig,
+    },
+    /// Stop an auto-bet strategy and refund its unspent budget
+    CancelStrategy {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        strategy_id: u64,
+    },
+    /// Stake tokens in a registered game running on the chain of `target_account`
+    /// `selection` is game specific (e.g. "Up" / "Down" for prediction rounds)
+    Play {
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: linera_sdk::abis::fungible::Account,
+        game_id: String,
+        selection: Option<String>,
+    },
+    /// Fund a budget that copies the bets of `leader` in the Rounds app running on the chain of `target_account`
+    /// Each copy stakes `ratio_bps` of the leader's amount (10_000 = same stake), capped by `max_per_bet`
+    Follow {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        leader: AccountOwner,
+        ratio_bps: u64,
+        max_per_bet: Amount,
+        budget: Amount,
+    },
+    /// Stop copying a leader and refund the unspent budget
+    Unfollow {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        follow_id: u64,
+    },
+    /// Predict the next `predictions.len()` rounds of the Rounds app running on the chain of `target_account` with one stake
+    PlaceParlay {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        amount: Amount,
+        predictions: Vec<Prediction>,
+    },
+    /// Add funds to the pool parlay winnings are paid from
+    /// Only the parlay pool operator configured in Rounds can withdraw them again
+    FundParlayPool {
+        owner: AccountOwner,
+        target_account: linera_sdk::abis::fungible::Account,
+        amount: Amount,
+    },
+    /// Settle the pending bets of queued bets that were placed or refunded (internal - called by Rounds app)
+    /// The refunds themselves are paid with `SendRewards`
+    SettleQueuedBets {
+        bets: Vec<QueuedBetOutcome>,
     },
 }
 
@@ -100,6 +267,52 @@ pub enum ExtendedResponse {
     Balance(Amount),
     ChainBalance(Amount),
     TickerSymbol(String),
+    /// Receipt for a bet placed on the same chain
+    BetReceipt(BetReceipt),
+    /// ID of a strategy created on the same chain
+    StrategyId(u64),
+    /// ID of a follow subscription created on the same chain
+    FollowId(u64),
+    /// ID of a parlay placed on the same chain
+    ParlayId(u64),
+}
+
+// ========================================
+// Game interface (games routed by Microbetreal)
+// ========================================
+
+/// Operations a `GameKind::Generic` game accepts from Microbetreal
+/// They must be the first variants of the game's own operation enum, in this order
+/// Rewards are paid back by calling `ExtendedOperation::SendReward(s)` on Microbetreal
+#[derive(Debug, Deserialize, Serialize)]
+pub enum GameOperation {
+    /// Token the game takes stakes in (None = Native)
+    GetCurrency,
+    /// Register a stake already held by Microbetreal's escrow on this chain
+    AcceptStake {
+        owner: AccountOwner,
+        amount: Amount,
+        source_chain_id: Option<String>,
+        selection: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum GameResponse {
+    Ok,
+    Currency(Option<ApplicationId>),
+}
+
+pub struct GameAbi;
+
+impl ContractAbi for GameAbi {
+    type Operation = GameOperation;
+    type Response = GameResponse;
+}
+
+impl ServiceAbi for GameAbi {
+    type Query = Request;
+    type QueryResponse = Response;
 }
 
 pub struct ExtendedNativeFungibleTokenAbi;
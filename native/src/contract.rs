@@ -5,15 +5,20 @@
 
 mod state;
 
+use std::collections::BTreeMap;
+
 use linera_sdk::{
     abis::fungible::{
-        Account as FungibleAccount, InitialState, Parameters,
+        Account as FungibleAccount, Parameters,
     },
-    linera_base_types::{Account, AccountOwner, ChainId, WithContractAbi},
+    linera_base_types::{Account, AccountOwner, Amount, ChainId, StreamName, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use native::{Message, TICKER_SYMBOL, NativeAbi, NativeOperation, NativeResponse};
+use native::{
+    Allowance, Message, FAUCET_DAY_MICROS, MAX_MEMO_LENGTH, MINT_STREAM_NAME, TICKER_SYMBOL, NativeAbi, NativeEvent,
+    NativeInitialState, NativeOperation, NativeResponse, TransferDirection, TransferRecord,
+};
 use self::state::NativeState;
 
 pub struct NativeContract {
@@ -30,8 +35,8 @@ impl WithContractAbi for NativeContract {
 impl Contract for NativeContract {
     type Message = Message;
     type Parameters = Parameters;
-    type InstantiationArgument = InitialState;
-    type EventValue = ();
+    type InstantiationArgument = NativeInitialState;
+    type EventValue = NativeEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = NativeState::load(runtime.root_view_storage_context())
@@ -47,8 +52,13 @@ impl Contract for NativeContract {
             "Only NAT is accepted as ticker symbol"
         );
         
+        self.state.minters.set(initial_state.minters);
+        self.state.supply_cap.set(initial_state.supply_cap);
+        self.state.faucet_config.set(initial_state.faucet);
+
         // Initialize balances
         for (owner, amount) in initial_state.accounts {
+            self.add_minted(amount);
             let account = Account {
                 chain_id: self.runtime.chain_id(),
                 owner,
@@ -78,21 +88,169 @@ impl Contract for NativeContract {
                 amount,
                 target_account,
             } => {
+                self.transfer_with_memo(owner, amount, target_account, None).await;
+                NativeResponse::Ok
+            }
+
+            NativeOperation::TransferWithMemo {
+                owner,
+                amount,
+                target_account,
+                memo,
+            } => {
+                self.transfer_with_memo(owner, amount, target_account, memo).await;
+                NativeResponse::Ok
+            }
+
+            NativeOperation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => {
+                self.approve(owner, spender, allowance, None).await;
+                NativeResponse::Ok
+            }
+
+            NativeOperation::ApproveUntil {
+                owner,
+                spender,
+                allowance,
+                expiry,
+            } => {
+                self.approve(owner, spender, allowance, expiry).await;
+                NativeResponse::Ok
+            }
+
+            NativeOperation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                // Only the allowance of the authorized spender can be used, so an application the owner
+                // interacts with cannot spend allowances granted to someone else
                 self.runtime
-                    .check_account_permission(owner)
-                    .expect("Permission for Transfer operation");
+                    .check_account_permission(spender)
+                    .expect("Permission for TransferFrom operation");
+
+                let mut allowance = self
+                    .state
+                    .allowances
+                    .get(&(owner, spender))
+                    .await
+                    .expect("Failed to load allowance")
+                    .expect("No allowance granted by owner to spender");
+                if let Some(expiry) = allowance.expiry {
+                    assert!(
+                        self.runtime.system_time().micros() <= expiry,
+                        "Allowance has expired"
+                    );
+                }
+                allowance.amount = allowance
+                    .amount
+                    .try_sub(amount)
+                    .expect("Amount exceeds allowance");
+                self.state
+                    .allowances
+                    .insert(&(owner, spender), allowance)
+                    .expect("Failed to store allowance");
 
                 let target_account = self.normalize_account(target_account);
-                self.runtime.transfer(owner, target_account, amount);
-                
-                // Send notify message for cross-chain transfers
-                if target_account.chain_id != self.runtime.chain_id() {
-                    self.transfer(target_account.chain_id);
+                let escrow = self.escrow_owner();
+                self.runtime.transfer(escrow, target_account, amount);
+                self.record_outgoing(owner, target_account, amount, None).await;
+
+                NativeResponse::Ok
+            }
+
+            NativeOperation::BatchTransfer {
+                owner,
+                transfers,
+                memo,
+            } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for BatchTransfer operation");
+                Self::check_memo(&memo);
+
+                let total = transfers
+                    .iter()
+                    .try_fold(Amount::ZERO, |total, (_, amount)| total.try_add(*amount))
+                    .expect("Batch total overflow");
+                assert!(
+                    self.runtime.owner_balance(owner) >= total,
+                    "Insufficient balance for batch transfer"
+                );
+
+                let this_chain = self.runtime.chain_id();
+                let source = Account {
+                    chain_id: this_chain,
+                    owner,
+                };
+                let mut remote: BTreeMap<ChainId, Vec<(AccountOwner, Amount)>> = BTreeMap::new();
+                let recipients = transfers.len() as u64;
+                let mut first_recipient = None;
+
+                for (target_account, amount) in transfers {
+                    let target_account = self.normalize_account(target_account);
+                    self.runtime.transfer(owner, target_account, amount);
+                    first_recipient.get_or_insert(target_account);
+
+                    if target_account.chain_id == this_chain {
+                        self.record_transfer(target_account.owner, TransferDirection::Incoming, source, amount, memo.clone())
+                            .await;
+                    } else {
+                        remote
+                            .entry(target_account.chain_id)
+                            .or_default()
+                            .push((target_account.owner, amount));
+                    }
+                }
+
+                // One history entry for the payer, covering the whole batch
+                if let Some(first_recipient) = first_recipient {
+                    let record = TransferRecord {
+                        direction: TransferDirection::Outgoing,
+                        counterparty_chain_id: first_recipient.chain_id,
+                        counterparty: first_recipient.owner,
+                        amount: total,
+                        memo: memo.clone(),
+                        timestamp: self.runtime.system_time().micros(),
+                        batch_recipients: Some(recipients),
+                    };
+                    self.push_transfer_record(owner, record).await;
+                }
+
+                // One notice per destination chain
+                for (chain_id, transfers) in remote {
+                    let message = Message::BatchTransferReceived {
+                        source: linera_sdk::abis::fungible::Account {
+                            chain_id: source.chain_id,
+                            owner: source.owner,
+                        },
+                        transfers,
+                        memo: memo.clone(),
+                    };
+                    self.runtime
+                        .prepare_message(message)
+                        .with_authentication()
+                        .send_to(chain_id);
                 }
 
                 NativeResponse::Ok
             }
 
+            NativeOperation::Allowance { owner, spender } => {
+                let allowance = self
+                    .state
+                    .allowances
+                    .get(&(owner, spender))
+                    .await
+                    .expect("Failed to load allowance");
+                let now = self.runtime.system_time().micros();
+                NativeResponse::Allowance(allowance.map(|allowance| allowance.spendable(now)).unwrap_or_default())
+            }
+
             NativeOperation::Claim {
                 source_account,
                 amount,
@@ -119,22 +277,99 @@ impl Contract for NativeContract {
                     owner: AccountOwner::CHAIN,
                 };
                 self.runtime.transfer(owner, target_account, balance);
+                let total_burned = self.state.total_burned.get().saturating_add(balance);
+                self.state.total_burned.set(total_burned);
                 NativeResponse::Ok
             }
 
             NativeOperation::Mint { owner, amount } => {
+                let minter = self.check_minter();
+                self.add_minted(amount);
+
                 let target_account = Account {
                     chain_id: self.runtime.chain_id(),
                     owner,
                 };
                 self.runtime.transfer(AccountOwner::CHAIN, target_account, amount);
+                self.runtime.emit(
+                    StreamName(MINT_STREAM_NAME.to_vec()),
+                    &NativeEvent::Minted { minter, owner, amount },
+                );
+                NativeResponse::Ok
+            }
+
+            NativeOperation::Faucet => {
+                let owner = self
+                    .runtime
+                    .authenticated_signer()
+                    .expect("Faucet requires an authenticated owner");
+                let config = self
+                    .state
+                    .faucet_config
+                    .get()
+                    .clone()
+                    .expect("Faucet is disabled");
+                let now = self.runtime.system_time().micros();
+
+                // Per-owner rate limit
+                let last_claim = self
+                    .state
+                    .faucet_last_claim
+                    .get(&owner)
+                    .await
+                    .expect("Failed to load faucet claim");
+                if let Some(last_claim) = last_claim {
+                    let next_claim = last_claim.saturating_add(config.period_secs.saturating_mul(1_000_000));
+                    assert!(now >= next_claim, "Faucet already claimed, next claim at {}", next_claim);
+                }
+
+                // Global daily cap
+                let day = now / FAUCET_DAY_MICROS;
+                if *self.state.faucet_day.get() != day {
+                    self.state.faucet_day.set(day);
+                    self.state.faucet_claimed_today.set(Amount::ZERO);
+                }
+                let claimed_today = self.state.faucet_claimed_today.get().saturating_add(config.amount);
+                assert!(claimed_today <= config.daily_cap, "Faucet daily cap reached");
+                self.state.faucet_claimed_today.set(claimed_today);
+                self.state
+                    .faucet_last_claim
+                    .insert(&owner, now)
+                    .expect("Failed to store faucet claim");
+
+                self.add_minted(config.amount);
+                let target_account = Account {
+                    chain_id: self.runtime.chain_id(),
+                    owner,
+                };
+                self.runtime.transfer(AccountOwner::CHAIN, target_account, config.amount);
+                self.runtime.emit(
+                    StreamName(MINT_STREAM_NAME.to_vec()),
+                    &NativeEvent::FaucetClaimed { owner, amount: config.amount },
+                );
                 NativeResponse::Ok
             }
         }
     }
 
-    async fn execute_message(&mut self, _message: Self::Message) {
-        // Auto-deploy on notify
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            Message::Notify => {
+                // Auto-deploy on notify
+            }
+            Message::TransferReceived { source, recipient, amount, memo } => {
+                let source = self.normalize_account(source);
+                self.record_transfer(recipient, TransferDirection::Incoming, source, amount, memo)
+                    .await;
+            }
+            Message::BatchTransferReceived { source, transfers, memo } => {
+                let source = self.normalize_account(source);
+                for (recipient, amount) in transfers {
+                    self.record_transfer(recipient, TransferDirection::Incoming, source, amount, memo.clone())
+                        .await;
+                }
+            }
+        }
     }
 
     async fn store(mut self) {
@@ -165,6 +400,189 @@ impl NativeContract {
         }
     }
 
+    /// Account of this application, holding the approved allowances
+    fn escrow_owner(&mut self) -> AccountOwner {
+        AccountOwner::from(self.runtime.application_id().forget_abi())
+    }
+
+    async fn transfer_with_memo(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: FungibleAccount,
+        memo: Option<String>,
+    ) {
+        self.runtime
+            .check_account_permission(owner)
+            .expect("Permission for Transfer operation");
+        Self::check_memo(&memo);
+
+        let target_account = self.normalize_account(target_account);
+        self.runtime.transfer(owner, target_account, amount);
+        self.record_outgoing(owner, target_account, amount, memo).await;
+    }
+
+    /// Replaces the allowance of `spender` on `owner`'s tokens, moving the funds in or out of escrow
+    async fn approve(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+        expiry: Option<u64>,
+    ) {
+        self.runtime
+            .check_account_permission(owner)
+            .expect("Permission for Approve operation");
+
+        let this_chain = self.runtime.chain_id();
+        let escrow = self.escrow_owner();
+
+        // Return whatever is left of the previous allowance
+        let previous = self
+            .state
+            .allowances
+            .get(&(owner, spender))
+            .await
+            .expect("Failed to load allowance");
+        if let Some(previous) = previous {
+            if !previous.amount.is_zero() {
+                let owner_account = Account { chain_id: this_chain, owner };
+                self.runtime.transfer(escrow, owner_account, previous.amount);
+            }
+        }
+
+        if amount.is_zero() {
+            self.state
+                .allowances
+                .remove(&(owner, spender))
+                .expect("Failed to remove allowance");
+        } else {
+            let escrow_account = Account { chain_id: this_chain, owner: escrow };
+            self.runtime.transfer(owner, escrow_account, amount);
+            self.state
+                .allowances
+                .insert(&(owner, spender), Allowance { owner, spender, amount, expiry })
+                .expect("Failed to store allowance");
+        }
+    }
+
+    /// Records an outgoing transfer of `owner` and lets the recipient's side record it too
+    async fn record_outgoing(
+        &mut self,
+        owner: AccountOwner,
+        target_account: Account,
+        amount: Amount,
+        memo: Option<String>,
+    ) {
+        let source = Account {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        };
+        self.record_transfer(owner, TransferDirection::Outgoing, target_account, amount, memo.clone())
+            .await;
+
+        if target_account.chain_id == self.runtime.chain_id() {
+            self.record_transfer(target_account.owner, TransferDirection::Incoming, source, amount, memo)
+                .await;
+        } else {
+            // Let the recipient's chain record the transfer
+            let message = Message::TransferReceived {
+                source: linera_sdk::abis::fungible::Account {
+                    chain_id: source.chain_id,
+                    owner: source.owner,
+                },
+                recipient: target_account.owner,
+                amount,
+                memo,
+            };
+            self.runtime
+                .prepare_message(message)
+                .with_authentication()
+                .send_to(target_account.chain_id);
+        }
+    }
+
+    /// Returns the minter authorizing this call, panicking if it does not hold the minter role
+    fn check_minter(&mut self) -> AccountOwner {
+        let minters = self.state.minters.get();
+        if let Some(signer) = self.runtime.authenticated_signer() {
+            if minters.contains(&signer) {
+                return signer;
+            }
+        }
+        if let Some(caller_id) = self.runtime.authenticated_caller_id() {
+            let caller = AccountOwner::from(caller_id);
+            if minters.contains(&caller) {
+                return caller;
+            }
+        }
+        panic!("Mint is restricted to configured minters");
+    }
+
+    /// Counts `amount` as minted, enforcing the supply cap
+    fn add_minted(&mut self, amount: Amount) {
+        let total_minted = self
+            .state
+            .total_minted
+            .get()
+            .try_add(amount)
+            .expect("Minted supply overflow");
+        if let Some(cap) = *self.state.supply_cap.get() {
+            assert!(total_minted <= cap, "Mint would exceed the supply cap of {}", cap);
+        }
+        self.state.total_minted.set(total_minted);
+    }
+
+    /// Memos are stored in the transfer history of both sides, so their length is capped
+    fn check_memo(memo: &Option<String>) {
+        if let Some(memo) = memo {
+            assert!(
+                memo.len() <= MAX_MEMO_LENGTH,
+                "Memo exceeds {} bytes",
+                MAX_MEMO_LENGTH
+            );
+        }
+    }
+
+    async fn record_transfer(
+        &mut self,
+        owner: AccountOwner,
+        direction: TransferDirection,
+        counterparty: Account,
+        amount: Amount,
+        memo: Option<String>,
+    ) {
+        let record = TransferRecord {
+            direction,
+            counterparty_chain_id: counterparty.chain_id,
+            counterparty: counterparty.owner,
+            amount,
+            memo,
+            timestamp: self.runtime.system_time().micros(),
+            batch_recipients: None,
+        };
+        self.push_transfer_record(owner, record).await;
+    }
+
+    /// Appends an entry to the transfer history of `owner`
+    async fn push_transfer_record(&mut self, owner: AccountOwner, record: TransferRecord) {
+        let sequence = self
+            .state
+            .transfer_counts
+            .get(&owner)
+            .await
+            .expect("Failed to load transfer count")
+            .unwrap_or_default();
+        self.state
+            .transfer_records
+            .insert(&(owner, sequence), record)
+            .expect("Failed to store transfer history");
+        self.state
+            .transfer_counts
+            .insert(&owner, sequence + 1)
+            .expect("Failed to store transfer count");
+    }
+
     fn normalize_account(&self, account: FungibleAccount) -> Account {
         Account {
             chain_id: account.chain_id,
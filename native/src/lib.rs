@@ -3,15 +3,87 @@
 
 /*! Pure Native Fungible Token Application - No Game Logic */
 
+use std::collections::BTreeMap;
+
 use async_graphql::{Request, Response, SimpleObject, InputObject};
 use linera_sdk::linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi, ChainId};
 use serde::{Deserialize, Serialize};
 
 pub const TICKER_SYMBOL: &str = "NAT";
 
+/// Instantiation argument: initial balances, the accounts allowed to mint and an optional supply cap
+/// Compatible with the standard fungible `InitialState` (`{"accounts": {...}}`)
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NativeInitialState {
+    pub accounts: BTreeMap<AccountOwner, Amount>,
+    #[serde(default)]
+    pub minters: Vec<AccountOwner>,
+    /// Maximum amount that can ever be minted (initial balances included); None means unlimited
+    #[serde(default)]
+    pub supply_cap: Option<Amount>,
+    /// Test-token faucet settings; None disables the faucet
+    #[serde(default)]
+    pub faucet: Option<FaucetConfig>,
+}
+
+/// Length of the window the faucet daily cap applies to
+pub const FAUCET_DAY_MICROS: u64 = 86_400_000_000;
+
+/// Faucet settings: each owner can claim `amount` once per `period_secs`,
+/// and at most `daily_cap` is handed out in total per day
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct FaucetConfig {
+    pub amount: Amount,
+    pub period_secs: u64,
+    pub daily_cap: Amount,
+}
+
+/// Faucet availability for an owner
+#[derive(Clone, Debug, SimpleObject)]
+pub struct FaucetStatus {
+    pub amount: Amount,
+    /// Timestamp (micros) of the owner's last claim
+    pub last_claim_at: Option<u64>,
+    /// Earliest timestamp (micros) at which the owner can claim again
+    pub next_claim_at: u64,
+    pub can_claim_now: bool,
+    /// Amount still available today under the global daily cap
+    pub remaining_today: Amount,
+}
+
+/// Events emitted by the Native app
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum NativeEvent {
+    Minted {
+        minter: AccountOwner,
+        owner: AccountOwner,
+        amount: Amount,
+    },
+    FaucetClaimed {
+        owner: AccountOwner,
+        amount: Amount,
+    },
+}
+
+/// Stream the mint events are emitted on
+pub const MINT_STREAM_NAME: &[u8] = b"mint";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
     Notify,
+    /// Cross-chain transfer notice, so the recipient's chain can record it in its history
+    TransferReceived {
+        source: linera_sdk::abis::fungible::Account,
+        recipient: AccountOwner,
+        amount: Amount,
+        memo: Option<String>,
+    },
+    /// All transfers of a batch going to one chain, in a single notice
+    BatchTransferReceived {
+        source: linera_sdk::abis::fungible::Account,
+        transfers: Vec<(AccountOwner, Amount)>,
+        memo: Option<String>,
+    },
 }
 
 // GraphQL Input type для Account
@@ -21,33 +93,110 @@ pub struct AccountInput {
     pub owner: AccountOwner,
 }
 
+// GraphQL Input type for one recipient of a batch transfer
+#[derive(InputObject, Debug, Clone)]
+pub struct BatchTransferInput {
+    pub target_account: AccountInput,
+    pub amount: String,
+}
+
 #[derive(SimpleObject)]
 pub struct AccountEntry {
     pub key: AccountOwner,
     pub value: Amount,
 }
 
+/// Budget an owner approved for a spender
+/// The approved amount is held by the Native application until spent or released
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Allowance {
+    pub owner: AccountOwner,
+    pub spender: AccountOwner,
+    pub amount: Amount,
+    /// Timestamp (micros) after which the allowance can no longer be spent
+    pub expiry: Option<u64>,
+}
+
+impl Allowance {
+    /// Amount the spender can still take at time `now` (zero once the allowance has expired)
+    pub fn spendable(&self, now: u64) -> Amount {
+        match self.expiry {
+            Some(expiry) if now > expiry => Amount::ZERO,
+            _ => self.amount,
+        }
+    }
+}
+
+/// Direction of a transfer from the owner's point of view
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, async_graphql::Enum)]
+pub enum TransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// Maximum length (in bytes) of a transfer memo
+pub const MAX_MEMO_LENGTH: usize = 128;
+
+/// An entry of an owner's transfer history
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct TransferRecord {
+    pub direction: TransferDirection,
+    pub counterparty_chain_id: ChainId,
+    pub counterparty: AccountOwner,
+    pub amount: Amount,
+    /// Optional reference, e.g. `round:123:payout`
+    pub memo: Option<String>,
+    pub timestamp: u64,
+    /// Number of recipients when the entry records a whole batch transfer
+    /// (the amount is then the batch total and the counterparty its first recipient)
+    #[serde(default)]
+    pub batch_recipients: Option<u64>,
+}
+
 // Pure token operations - NO prediction/betting logic
+//
+// The first six variants mirror `linera_sdk::abis::fungible::FungibleOperation` in order and
+// shape, so clients built for the standard `FungibleTokenAbi` serialize operations this app
+// understands. Extensions must only ever be appended after them.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum NativeOperation {
     /// Get balance for an account owner
     Balance { owner: AccountOwner },
-    /// Get the chain balance
-    ChainBalance,
     /// Get the ticker symbol
     TickerSymbol,
+    /// Approve `spender` to move up to `allowance` of the owner's tokens
+    /// The amount is moved into the Native application account; any previous unspent
+    /// allowance for the same spender is returned to the owner. Approve 0 to revoke.
+    Approve {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+    },
     /// Transfer tokens between accounts (pure transfer, no prediction)
     Transfer {
         owner: AccountOwner,
         amount: Amount,
         target_account: linera_sdk::abis::fungible::Account,
     },
+    /// Spend from an allowance granted by `owner` to `spender`
+    /// `spender` must authorize the call (as the signer, or as the calling application);
+    /// allowances granted to other spenders are never used
+    TransferFrom {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+        target_account: linera_sdk::abis::fungible::Account,
+    },
     /// Claim tokens from another chain
     Claim {
         source_account: linera_sdk::abis::fungible::Account,
         amount: Amount,
         target_account: linera_sdk::abis::fungible::Account,
     },
+
+    // Native extensions
+    /// Get the chain balance
+    ChainBalance,
     /// Withdraw all tokens to chain account
     Withdraw,
     /// Mint new tokens to an account
@@ -55,14 +204,44 @@ pub enum NativeOperation {
         owner: AccountOwner,
         amount: Amount,
     },
+    /// Claim the faucet amount for the signer (rate-limited)
+    Faucet,
+    /// Transfer with a reference recorded in the transfer history (e.g. `round:123:payout`)
+    TransferWithMemo {
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: linera_sdk::abis::fungible::Account,
+        memo: Option<String>,
+    },
+    /// Same as `Approve`, but the allowance can no longer be spent after `expiry` (micros)
+    ApproveUntil {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+        expiry: Option<u64>,
+    },
+    /// Transfer to many recipients at once; the balance is checked once for the total
+    /// and cross-chain notices are grouped per destination chain
+    BatchTransfer {
+        owner: AccountOwner,
+        transfers: Vec<(linera_sdk::abis::fungible::Account, Amount)>,
+        memo: Option<String>,
+    },
+    /// Get the remaining allowance of `spender` on `owner`'s tokens (zero once it has expired)
+    Allowance {
+        owner: AccountOwner,
+        spender: AccountOwner,
+    },
 }
 
+// The first three variants mirror `linera_sdk::abis::fungible::FungibleResponse`
 #[derive(Debug, Deserialize, Serialize)]
 pub enum NativeResponse {
     Ok,
     Balance(Amount),
-    ChainBalance(Amount),
     TickerSymbol(String),
+    ChainBalance(Amount),
+    Allowance(Amount),
 }
 
 pub struct NativeAbi;
@@ -3,14 +3,22 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod state;
+
 use std::sync::Arc;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{AccountOwner, WithServiceAbi},
+    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    views::View,
     Service, ServiceRuntime,
 };
-use native::{AccountEntry, TICKER_SYMBOL, NativeAbi, NativeOperation, AccountInput};
+use native::{
+    AccountEntry, Allowance, BatchTransferInput, FAUCET_DAY_MICROS, TICKER_SYMBOL, NativeAbi,
+    NativeOperation, AccountInput, FaucetConfig, FaucetStatus, TransferRecord,
+};
+
+use self::state::NativeState;
 
 linera_sdk::service!(NativeService);
 
@@ -32,8 +40,13 @@ impl Service for NativeService {
     }
 
     async fn handle_query(&self, request: Request) -> Response {
+        let state = NativeState::load(self.runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+
         let schema = Schema::build(
             QueryRoot {
+                state,
                 runtime: self.runtime.clone(),
             },
             MutationRoot {
@@ -79,6 +92,7 @@ impl Accounts {
 }
 
 struct QueryRoot {
+    state: NativeState,
     runtime: Arc<ServiceRuntime<NativeService>>,
 }
 
@@ -98,6 +112,108 @@ impl QueryRoot {
         let balance = self.runtime.chain_balance();
         Ok(balance.to_string())
     }
+
+    /// Accounts allowed to mint
+    async fn minters(&self) -> Vec<AccountOwner> {
+        self.state.minters.get().clone()
+    }
+
+    /// Maximum amount that can ever be minted (null if unlimited)
+    async fn supply_cap(&self) -> Option<Amount> {
+        *self.state.supply_cap.get()
+    }
+
+    /// Total amount minted so far, initial balances included
+    async fn total_minted(&self) -> Amount {
+        *self.state.total_minted.get()
+    }
+
+    /// Total amount burned through Withdraw
+    async fn total_burned(&self) -> Amount {
+        *self.state.total_burned.get()
+    }
+
+    /// Faucet settings (null if the faucet is disabled)
+    async fn faucet_config(&self) -> Option<FaucetConfig> {
+        self.state.faucet_config.get().clone()
+    }
+
+    /// When an owner can claim from the faucet next (null if the faucet is disabled)
+    async fn faucet_status(&self, owner: AccountOwner) -> Result<Option<FaucetStatus>, async_graphql::Error> {
+        let Some(config) = self.state.faucet_config.get().clone() else {
+            return Ok(None);
+        };
+        let now = self.runtime.system_time().micros();
+        let day = now / FAUCET_DAY_MICROS;
+
+        let claimed_today = if *self.state.faucet_day.get() == day {
+            *self.state.faucet_claimed_today.get()
+        } else {
+            Amount::ZERO
+        };
+        let remaining_today = config.daily_cap.saturating_sub(claimed_today);
+
+        let last_claim_at = self.state.faucet_last_claim.get(&owner).await?;
+        let mut next_claim_at = last_claim_at
+            .map(|last| last.saturating_add(config.period_secs.saturating_mul(1_000_000)))
+            .unwrap_or(now)
+            .max(now);
+        if remaining_today < config.amount {
+            // Daily cap exhausted - wait for the next day window
+            next_claim_at = next_claim_at.max((day + 1) * FAUCET_DAY_MICROS);
+        }
+
+        Ok(Some(FaucetStatus {
+            amount: config.amount,
+            last_claim_at,
+            next_claim_at,
+            can_claim_now: next_claim_at <= now,
+            remaining_today,
+        }))
+    }
+
+    /// Allowance granted by `owner` to `spender` (null if none)
+    /// The amount is zero once the allowance has expired, like the `Allowance` operation reports
+    async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Result<Option<Allowance>, async_graphql::Error> {
+        let now = self.runtime.system_time().micros();
+        Ok(self.state.allowances.get(&(owner, spender)).await?
+            .map(|allowance| Allowance { amount: allowance.spendable(now), ..allowance }))
+    }
+
+    /// All allowances granted by `owner` (expired ones with a zero amount)
+    async fn allowances(&self, owner: AccountOwner) -> Result<Vec<Allowance>, async_graphql::Error> {
+        let now = self.runtime.system_time().micros();
+        let mut allowances = Vec::new();
+        for (allowance_owner, spender) in self.state.allowances.indices().await? {
+            if allowance_owner != owner {
+                continue;
+            }
+            if let Some(allowance) = self.state.allowances.get(&(allowance_owner, spender)).await? {
+                allowances.push(Allowance { amount: allowance.spendable(now), ..allowance });
+            }
+        }
+        Ok(allowances)
+    }
+
+    /// Transfers sent and received by an owner on this chain, with their memos, oldest first
+    /// Returns up to `limit` entries (default 100) starting at sequence number `offset`
+    async fn transfer_history(
+        &self,
+        owner: AccountOwner,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TransferRecord>, async_graphql::Error> {
+        let count = self.state.transfer_counts.get(&owner).await?.unwrap_or_default();
+        let start = offset.unwrap_or_default().min(count);
+        let end = start.saturating_add(limit.unwrap_or(100)).min(count);
+        let mut records = Vec::new();
+        for sequence in start..end {
+            if let Some(record) = self.state.transfer_records.get(&(owner, sequence)).await? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
 }
 
 struct MutationRoot {
@@ -126,6 +242,7 @@ impl MutationRoot {
         owner: AccountOwner,
         amount: String,
         target_account: AccountInput,
+        memo: Option<String>,
     ) -> String {
         use linera_sdk::linera_base_types::Amount;
         let fungible_account = linera_sdk::abis::fungible::Account {
@@ -133,11 +250,21 @@ impl MutationRoot {
             owner: target_account.owner,
         };
         
-        self.runtime.schedule_operation(&NativeOperation::Transfer {
-            owner,
-            amount: amount.parse::<Amount>().unwrap_or_default(),
-            target_account: fungible_account,
-        });
+        let amount = amount.parse::<Amount>().unwrap_or_default();
+        let operation = match memo {
+            Some(memo) => NativeOperation::TransferWithMemo {
+                owner,
+                amount,
+                target_account: fungible_account,
+                memo: Some(memo),
+            },
+            None => NativeOperation::Transfer {
+                owner,
+                amount,
+                target_account: fungible_account,
+            },
+        };
+        self.runtime.schedule_operation(&operation);
         "Transfer operation scheduled".to_string()
     }
 
@@ -170,6 +297,82 @@ impl MutationRoot {
         "Withdraw operation scheduled successfully".to_string()
     }
 
+    async fn approve(
+        &self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: String,
+        expiry: Option<u64>,
+    ) -> String {
+        let allowance = allowance.parse::<Amount>().unwrap_or_default();
+        let operation = match expiry {
+            Some(expiry) => NativeOperation::ApproveUntil {
+                owner,
+                spender,
+                allowance,
+                expiry: Some(expiry),
+            },
+            None => NativeOperation::Approve {
+                owner,
+                spender,
+                allowance,
+            },
+        };
+        self.runtime.schedule_operation(&operation);
+        "Approve operation scheduled successfully".to_string()
+    }
+
+    async fn transfer_from(
+        &self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: String,
+        target_account: AccountInput,
+    ) -> String {
+        let fungible_account = linera_sdk::abis::fungible::Account {
+            chain_id: target_account.chain_id,
+            owner: target_account.owner,
+        };
+
+        self.runtime.schedule_operation(&NativeOperation::TransferFrom {
+            owner,
+            spender,
+            amount: amount.parse::<Amount>().unwrap_or_default(),
+            target_account: fungible_account,
+        });
+        "TransferFrom operation scheduled successfully".to_string()
+    }
+
+    async fn batch_transfer(
+        &self,
+        owner: AccountOwner,
+        transfers: Vec<BatchTransferInput>,
+        memo: Option<String>,
+    ) -> String {
+        let transfers = transfers
+            .into_iter()
+            .map(|transfer| {
+                let fungible_account = linera_sdk::abis::fungible::Account {
+                    chain_id: transfer.target_account.chain_id,
+                    owner: transfer.target_account.owner,
+                };
+                (fungible_account, transfer.amount.parse::<Amount>().unwrap_or_default())
+            })
+            .collect();
+
+        self.runtime.schedule_operation(&NativeOperation::BatchTransfer {
+            owner,
+            transfers,
+            memo,
+        });
+        "BatchTransfer operation scheduled successfully".to_string()
+    }
+
+    async fn faucet(&self) -> String {
+        self.runtime.schedule_operation(&NativeOperation::Faucet);
+        "Faucet operation scheduled successfully".to_string()
+    }
+
     async fn mint(&self, owner: AccountOwner, amount: String) -> String {
         use linera_sdk::linera_base_types::Amount;
         self.runtime.schedule_operation(&NativeOperation::Mint {
@@ -1,13 +1,36 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
+use linera_sdk::linera_base_types::{AccountOwner, Amount};
+use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
+use native::{Allowance, FaucetConfig, TransferRecord};
 
-/// Pure token state - no game logic
-/// Note: We need at least one field for RootView, so we use a dummy field
+/// Pure token state - balances live in the runtime, only bookkeeping is stored here
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct NativeState {
-    /// Dummy field (RootView requires at least one field)
-    pub _dummy: RegisterView<()>,
+    /// Unbounded per-owner transfer history, no longer written (superseded by `transfer_records`)
+    pub transfer_history: MapView<AccountOwner, Vec<TransferRecord>>,
+    /// Accounts allowed to mint (configured at instantiation)
+    pub minters: RegisterView<Vec<AccountOwner>>,
+    /// Maximum amount that can ever be minted; None means unlimited
+    pub supply_cap: RegisterView<Option<Amount>>,
+    /// Total amount minted so far (initial balances included)
+    pub total_minted: RegisterView<Amount>,
+    /// Total amount burned through Withdraw
+    pub total_burned: RegisterView<Amount>,
+    /// Faucet settings (None if the faucet is disabled)
+    pub faucet_config: RegisterView<Option<FaucetConfig>>,
+    /// Timestamp (micros) of each owner's last faucet claim
+    pub faucet_last_claim: MapView<AccountOwner, u64>,
+    /// Day index (timestamp / FAUCET_DAY_MICROS) the daily counter refers to
+    pub faucet_day: RegisterView<u64>,
+    /// Amount handed out by the faucet during `faucet_day`
+    pub faucet_claimed_today: RegisterView<Amount>,
+    /// Allowances keyed by (owner, spender)
+    pub allowances: MapView<(AccountOwner, AccountOwner), Allowance>,
+    /// Transfer history of each owner on this chain (with optional memos), keyed by (owner, sequence number)
+    pub transfer_records: MapView<(AccountOwner, u64), TransferRecord>,
+    /// Number of transfer history entries of each owner
+    pub transfer_counts: MapView<AccountOwner, u64>,
 }
@@ -6,12 +6,12 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::{Amount, ApplicationId, WithContractAbi},
+    linera_base_types::{AccountOwner, Amount, ApplicationId, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 use rounds::{RoundsAbi, RoundsOperation, RoundsResponse, Prediction, Message};
-use self::state::{RoundsState, PredictionRound, RoundStatus, Prediction as StatePrediction};
+use self::state::{RoundsState, PredictionRound, RoundStatus, Prediction as StatePrediction, AutoBetStrategy, FollowSubscription};
 
 // Conversion functions between lib types and state types
 fn prediction_from_lib(lib_prediction: Prediction) -> StatePrediction {
@@ -28,6 +28,13 @@ fn prediction_to_lib(prediction: StatePrediction) -> Prediction {
     }
 }
 
+fn prediction_to_abi(prediction: StatePrediction) -> native_fungible_abi::Prediction {
+    match prediction {
+        StatePrediction::Up => native_fungible_abi::Prediction::Up,
+        StatePrediction::Down => native_fungible_abi::Prediction::Down,
+    }
+}
+
 fn round_status_to_lib(status: RoundStatus) -> rounds::RoundStatus {
     match status {
         RoundStatus::Active => rounds::RoundStatus::Active,
@@ -96,6 +103,8 @@ impl Contract for RoundsContract {
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
             RoundsOperation::SetMicrobetAppId { microbet_app_id } => {
+                // Every escrow gate trusts this ID, so it can only be set once
+                assert!(self.state.microbet_app_id.get().is_none(), "Microbetreal app ID is already set");
                 match microbet_app_id.parse::<ApplicationId>() {
                     Ok(app_id) => {
                         let typed_app_id: ApplicationId<native_fungible_abi::ExtendedNativeFungibleTokenAbi> = app_id.with_abi();
@@ -116,7 +125,11 @@ impl Contract for RoundsContract {
             RoundsOperation::CreateRound => {
                 let timestamp = self.runtime.system_time().micros();
                 match self.state.create_round(timestamp).await {
-                    Ok(round_id) => RoundsResponse::RoundId(round_id),
+                    Ok(round_id) => {
+                        self.refund_failed_bets();
+                        self.refund_stopped_strategies().await;
+                        RoundsResponse::RoundId(round_id)
+                    }
                     Err(e) => panic!("Failed to create round: {}", e),
                 }
             }
@@ -124,7 +137,11 @@ impl Contract for RoundsContract {
             RoundsOperation::CloseRound { closing_price } => {
                 let timestamp = self.runtime.system_time().micros();
                 match self.state.close_round(closing_price, timestamp).await {
-                    Ok(new_round_id) => RoundsResponse::RoundId(new_round_id),
+                    Ok(new_round_id) => {
+                        self.refund_failed_bets();
+                        self.refund_stopped_strategies().await;
+                        RoundsResponse::RoundId(new_round_id)
+                    }
                     Err(e) => panic!("Failed to close round: {}", e),
                 }
             }
@@ -152,19 +169,29 @@ impl Contract for RoundsContract {
                                         let microbetreal_app_id = self.state.microbet_app_id.get()
                                             .expect("Microbetreal app ID not set");
                                         
+                                        // Distribute all rewards in a single batched call
+                                        let rewards: Vec<_> = results.iter()
+                                            .filter(|(_, _, winnings, _, _)| *winnings > Amount::ZERO)
+                                            .map(|(owner, _, winnings, _, source_chain_id)| native_fungible_abi::RewardPayout {
+                                                recipient: *owner,
+                                                amount: *winnings,
+                                                source_chain_id: source_chain_id.clone(),
+                                            })
+                                            .collect();
+                                        if !rewards.is_empty() {
+                                            let _response: native_fungible_abi::ExtendedResponse = self.runtime.call_application(
+                                                true, // authenticated
+                                                microbetreal_app_id,
+                                                &native_fungible_abi::ExtendedOperation::SendRewards {
+                                                    rewards,
+                                                    round_id: Some(round.id),
+                                                    memo: Some(format!("round:{}:payout", round.id)),
+                                                    currency_app_id: Some(params.currency()),
+                                                },
+                                            );
+                                        }
+                                        
                                         for (owner, bet_amount, winnings, is_win, source_chain_id) in results {
-                                            // Distribute rewards if any
-                                            if winnings > Amount::ZERO {
-                                                let _response: native_fungible_abi::ExtendedResponse = self.runtime.call_application(
-                                                    true, // authenticated
-                                                    microbetreal_app_id,
-                                                    &native_fungible_abi::ExtendedOperation::SendReward {
-                                                        recipient: owner,
-                                                        amount: winnings,
-                                                        source_chain_id: source_chain_id.clone(),
-                                                    },
-                                                );
-                                            }
 
                                             // Update leaderboard stats (for everyone)
                                             let player_chain_id_str = source_chain_id.clone().unwrap_or_else(|| self.runtime.chain_id().to_string());
@@ -224,6 +251,8 @@ impl Contract for RoundsContract {
                                             }
                                         }
                                         
+                                        self.pay_parlays().await;
+                                        self.refund_stopped_strategies().await;
                                         RoundsResponse::Ok
                                     },
                                     Err(e) => panic!("Failed to resolve round: {}", e),
@@ -236,13 +265,150 @@ impl Contract for RoundsContract {
                 }
             }
 
-            RoundsOperation::PlaceBet { owner, amount, prediction, source_chain_id } => {
+            RoundsOperation::PlaceBet { owner, amount, prediction, source_chain_id, expected_round_id, min_payout_multiplier, deadline } => {
+                self.check_microbet_caller();
+                // Slippage protection: reject the bet if the round or odds moved away from what the bettor saw
+                if expected_round_id.is_some() || min_payout_multiplier.is_some() || deadline.is_some() {
+                    let active_round = match self.state.get_active_round().await {
+                        Ok(Some(round_id)) => self.state.get_round(round_id).await.ok().flatten(),
+                        _ => None,
+                    };
+                    let Some(active_round) = active_round else {
+                        panic!("Failed to place bet: No active round");
+                    };
+                    let now = self.runtime.system_time().micros();
+                    if let Err(e) = rounds::check_bet_conditions(
+                        &prediction_round_to_lib(active_round),
+                        amount,
+                        prediction,
+                        expected_round_id,
+                        min_payout_multiplier,
+                        deadline,
+                        now,
+                    ) {
+                        panic!("Failed to place bet: {}", e);
+                    }
+                }
                 let state_prediction = prediction_from_lib(prediction);
                 match self.state.place_bet(owner, amount, state_prediction, source_chain_id).await {
-                    Ok(()) => RoundsResponse::Ok,
+                    Ok((round_id, total_stake, sequence)) => {
+                        // Copy the bet for everyone following this owner
+                        if let Err(e) = self.state.place_follower_bets(owner, amount, state_prediction).await {
+                            eprintln!("Failed to copy bet of {:?}: {}", owner, e);
+                        }
+                        RoundsResponse::BetReceipt(rounds::BetReceipt {
+                            round_id,
+                            owner,
+                            prediction,
+                            amount,
+                            total_stake,
+                            sequence,
+                        })
+                    }
                     Err(e) => panic!("Failed to place bet: {}", e),
                 }
             }
+
+            RoundsOperation::QueueBet { owner, amount, prediction, source_chain_id } => {
+                self.check_microbet_caller();
+                let state_prediction = prediction_from_lib(prediction);
+                self.state.queue_bet(owner, amount, state_prediction, source_chain_id);
+                RoundsResponse::Ok
+            }
+            
+            RoundsOperation::RegisterStrategy { owner, config, source_chain_id } => {
+                self.check_microbet_caller();
+                let prediction = prediction_from_lib(config.prediction);
+                let strategy = AutoBetStrategy {
+                    id: 0,
+                    owner,
+                    kind: config.kind,
+                    prediction,
+                    base_amount: config.base_amount,
+                    max_amount: config.max_amount,
+                    budget: config.budget,
+                    loss_limit: config.loss_limit,
+                    next_amount: config.base_amount,
+                    total_lost: Amount::ZERO,
+                    total_won: Amount::ZERO,
+                    last_prediction: None,
+                    pending_bets: Vec::new(),
+                    active: true,
+                    source_chain_id,
+                };
+                match self.state.add_strategy(strategy) {
+                    Ok(strategy_id) => RoundsResponse::StrategyId(strategy_id),
+                    Err(e) => panic!("Failed to register strategy: {}", e),
+                }
+            }
+            
+            RoundsOperation::CancelStrategy { owner, strategy_id } => {
+                self.check_microbet_caller();
+                match self.state.cancel_strategy(owner, strategy_id).await {
+                    Ok((amount, source_chain_id)) => RoundsResponse::StrategyRefund { owner, amount, source_chain_id },
+                    Err(e) => panic!("Failed to cancel strategy: {}", e),
+                }
+            }
+            
+            RoundsOperation::Follow { follower, leader, ratio_bps, max_per_bet, budget, source_chain_id } => {
+                self.check_microbet_caller();
+                let follow = FollowSubscription {
+                    id: 0,
+                    follower,
+                    leader,
+                    ratio_bps,
+                    max_per_bet,
+                    budget,
+                    copied_bets: 0,
+                    copied_amount: Amount::ZERO,
+                    active: true,
+                    source_chain_id,
+                };
+                // Microbetreal already holds the budget, so it is told to return it instead of failing
+                match self.state.add_follow(follow).await {
+                    Ok(follow_id) => RoundsResponse::FollowId(follow_id),
+                    Err(e) => RoundsResponse::FollowRejected(e),
+                }
+            }
+            
+            RoundsOperation::Unfollow { follower, follow_id } => {
+                self.check_microbet_caller();
+                match self.state.remove_follow(follower, follow_id).await {
+                    Ok((amount, source_chain_id)) => RoundsResponse::FollowRefund { follower, amount, source_chain_id },
+                    Err(e) => panic!("Failed to unfollow: {}", e),
+                }
+            }
+            
+            RoundsOperation::PlaceParlay { owner, amount, predictions, source_chain_id } => {
+                self.check_microbet_caller();
+                let predictions = predictions.into_iter().map(prediction_from_lib).collect();
+                let multipliers = self.runtime.application_parameters().parlay_multipliers();
+                // Microbetreal already holds the stake, so it is told to return it instead of failing
+                match self.state.add_parlay(owner, amount, predictions, multipliers, source_chain_id).await {
+                    Ok(parlay_id) => RoundsResponse::ParlayId(parlay_id),
+                    Err(e) => RoundsResponse::ParlayRejected(e),
+                }
+            }
+            
+            RoundsOperation::FundParlayPool { amount } => {
+                self.check_microbet_caller();
+                self.state.fund_parlay_pool(amount);
+                RoundsResponse::Ok
+            }
+            
+            RoundsOperation::WithdrawParlayPool { amount } => {
+                let operator = self.runtime.application_parameters().parlay_pool_operator
+                    .expect("The parlay pool has no operator");
+                assert_eq!(
+                    self.runtime.authenticated_signer(),
+                    Some(operator),
+                    "Only the parlay pool operator can withdraw from the pool"
+                );
+                self.state.withdraw_parlay_pool(amount)
+                    .unwrap_or_else(|e| panic!("Failed to withdraw from the parlay pool: {}", e));
+                self.send_payouts(vec![(operator, amount, None)], "parlay:withdraw");
+                RoundsResponse::Ok
+            }
             
             RoundsOperation::ClaimWinnings { round_id } => {
                 // This operation is not used in the current design since rewards are auto-distributed
@@ -308,6 +474,10 @@ impl Contract for RoundsContract {
                 }
             }
             
+            RoundsOperation::GetCurrency => {
+                RoundsResponse::Currency(self.runtime.application_parameters().currency())
+            }
+            
             RoundsOperation::GetRoundWinners { round_id } => {
                 match self.state.get_round_winners(round_id).await {
                     Ok(winners) => {
@@ -362,3 +532,91 @@ source_chain_id,
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl RoundsContract {
+    /// Bets, strategies, follows and parlays hold funds in the Microbetreal escrow, so only Microbetreal may manage them
+    fn check_microbet_caller(&mut self) {
+        let microbet_app_id = self.state.microbet_app_id.get()
+            .expect("Microbetreal app ID not set");
+        assert_eq!(
+            self.runtime.authenticated_caller_id(),
+            Some(microbet_app_id.forget_abi()),
+            "Escrowed funds can only be managed through Microbetreal"
+        );
+    }
+
+    /// Send payouts from the Microbetreal escrow in a single batched call
+    fn send_payouts(&mut self, payouts: Vec<(AccountOwner, Amount, Option<String>)>, memo: &str) {
+        if payouts.is_empty() {
+            return;
+        }
+        let microbetreal_app_id = self.state.microbet_app_id.get()
+            .expect("Microbetreal app ID not set");
+        let rewards = payouts.into_iter()
+            .map(|(owner, amount, source_chain_id)| native_fungible_abi::RewardPayout {
+                recipient: owner,
+                amount,
+                source_chain_id,
+            })
+            .collect();
+        let params = self.runtime.application_parameters();
+        let _response: native_fungible_abi::ExtendedResponse = self.runtime.call_application(
+            true,
+            microbetreal_app_id,
+            &native_fungible_abi::ExtendedOperation::SendRewards {
+                rewards,
+                round_id: None,
+                memo: Some(memo.to_string()),
+                currency_app_id: Some(params.currency()),
+            },
+        );
+    }
+
+    /// Send the payouts of parlays settled by the last resolution
+    async fn pay_parlays(&mut self) {
+        let payouts = self.state.take_parlay_payouts();
+        self.send_payouts(payouts, "parlay:payout");
+    }
+
+    /// Refund the queued bets that could not be placed when the round opened,
+    /// then let Microbetreal settle the pending bets of every queued bet
+    fn refund_failed_bets(&mut self) {
+        let refunds = self.state.take_bet_refunds();
+        self.send_payouts(refunds, "bet:refund");
+        
+        let outcomes = self.state.take_queued_bet_outcomes();
+        if outcomes.is_empty() {
+            return;
+        }
+        let bets = outcomes.into_iter()
+            .map(|(bet, placed)| native_fungible_abi::QueuedBetOutcome {
+                owner: bet.owner,
+                amount: bet.amount,
+                prediction: prediction_to_abi(bet.prediction),
+                source_chain_id: bet.source_chain_id,
+                receipt: placed.map(|(round_id, total_stake, sequence)| native_fungible_abi::BetReceipt {
+                    round_id,
+                    owner: bet.owner,
+                    prediction: prediction_to_abi(bet.prediction),
+                    amount: bet.amount,
+                    total_stake,
+                    sequence,
+                }),
+            })
+            .collect();
+        let microbetreal_app_id = self.state.microbet_app_id.get()
+            .expect("Microbetreal app ID not set");
+        let _response: native_fungible_abi::ExtendedResponse = self.runtime.call_application(
+            true,
+            microbetreal_app_id,
+            &native_fungible_abi::ExtendedOperation::SettleQueuedBets { bets },
+        );
+    }
+
+    /// Return the unspent budget of strategies that stopped on their own to their owners
+    async fn refund_stopped_strategies(&mut self) {
+        let refunds = self.state.take_strategy_refunds().await
+            .unwrap_or_else(|e| panic!("Failed to collect strategy refunds: {}", e));
+        self.send_payouts(refunds, "strategy:refund");
+    }
+}
@@ -4,7 +4,7 @@
 /*! ABI of the Rounds Application for Prediction Game */
 
 use async_graphql::{Request, Response, SimpleObject};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi};
 use serde::{Deserialize, Serialize};
 
 // Prediction direction for the Up/Down game
@@ -57,6 +57,128 @@ pub struct ActiveBetInfo {
     pub prediction: Prediction,
 }
 
+// Receipt for a bet accepted into a round
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct BetReceipt {
+    pub round_id: u64,
+    pub owner: AccountOwner,
+    pub prediction: Prediction,
+    pub amount: Amount,       // Amount of this bet
+    pub total_stake: Amount,  // Cumulative stake of the owner on this side of the round
+    pub sequence: u64,        // Bet sequence number
+}
+
+// A bet waiting for the next round to open
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct QueuedBetInfo {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub prediction: Prediction,
+    pub source_chain_id: Option<String>,
+}
+
+// Rule an auto-bet strategy uses to pick each bet
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum StrategyKind {
+    FixedSide,        // Always bet `prediction`
+    Alternate,        // Start with `prediction`, then switch sides every round
+    FollowLastResult, // Bet on the result of the last resolved round (`prediction` until there is one)
+    Martingale,       // Bet `prediction`, doubling the stake after each loss up to `max_amount`
+}
+
+// Standing instruction to bet in every new round, funded by `budget`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoBetConfig {
+    pub kind: StrategyKind,
+    pub prediction: Prediction,
+    pub base_amount: Amount,
+    pub max_amount: Amount,         // Largest stake (Martingale)
+    pub budget: Amount,             // Funds set aside for the strategy's bets
+    pub loss_limit: Option<Amount>, // Stop once net losses reach this
+}
+
+// An auto-bet strategy and its progress
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AutoBetStrategyInfo {
+    pub id: u64,
+    pub owner: AccountOwner,
+    pub kind: StrategyKind,
+    pub prediction: Prediction,
+    pub base_amount: Amount,
+    pub max_amount: Amount,
+    pub budget: Amount,          // Left for future bets
+    pub loss_limit: Option<Amount>,
+    pub next_amount: Amount,
+    pub net_loss: Amount,        // Losses minus profits, zero while the strategy is ahead
+    pub total_lost: Amount,      // Stakes of settled bets that lost
+    pub total_won: Amount,       // Profits of settled bets that won
+    pub pending_bets: u64,       // Bets in rounds that are not resolved yet
+    pub active: bool,
+    pub source_chain_id: Option<String>,
+}
+
+// A follower copying the bets of a leader
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct FollowInfo {
+    pub id: u64,
+    pub follower: AccountOwner,
+    pub leader: AccountOwner,
+    pub ratio_bps: u64,          // Share of the leader's stake copied (10_000 = same stake)
+    pub max_per_bet: Amount,
+    pub budget: Amount,          // Left for future copied bets
+    pub copied_bets: u64,
+    pub copied_amount: Amount,
+    pub active: bool,
+    pub source_chain_id: Option<String>,
+}
+
+// Outcome of one leg of a parlay
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum LegOutcome {
+    Pending,
+    Won,
+    Lost,
+    Void, // The round ended in a tie
+}
+
+// Status of a parlay
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum ParlayStatus {
+    Open,
+    Won,      // Paid with the multiplier of its winning legs
+    Lost,     // The stake went to the parlay pool
+    Refunded, // Every leg was void
+}
+
+// One round predicted by a parlay
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ParlayLegInfo {
+    pub round_id: u64,
+    pub prediction: Prediction,
+    pub outcome: LegOutcome,
+}
+
+// A parlay and its progress
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ParlayInfo {
+    pub id: u64,
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub legs: Vec<ParlayLegInfo>,
+    pub status: ParlayStatus,
+    pub max_payout: Amount,      // Payout if every leg wins
+    pub payout: Option<Amount>,  // Paid once the parlay is settled
+    pub source_chain_id: Option<String>,
+}
+
+// Bankroll parlay winnings are paid from
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ParlayPoolInfo {
+    pub balance: Amount,
+    pub reserved: Amount,        // Winnings promised to open parlays
+    pub multipliers: Vec<u64>,   // Payout multiplier (basis points) by number of winning legs, from 1
+}
+
 // Winner information for a resolved round
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct RoundWinnerInfo {
@@ -66,6 +188,98 @@ pub struct RoundWinnerInfo {
     pub source_chain_id: Option<String>, // Add source chain ID for cross-chain winners
 }
 
+// Escrow balance compared with what the escrow still owes to bettors
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Solvency {
+    pub escrow_owner: Option<AccountOwner>,
+    pub escrow_balance: Option<Amount>,   // Tokens the escrow account holds, for all games (None if it could not be read)
+    pub escrow_credit: Option<Amount>,    // Stakes of this app in Microbetreal's escrow ledger (None if it could not be read)
+    pub liabilities: Amount,              // Stakes and budgets the escrow still owes (see `outstanding_liabilities`)
+    pub surplus: Option<Amount>,          // min(escrow_balance, escrow_credit) - liabilities (zero if insolvent)
+    pub is_solvent: Option<bool>,
+}
+
+/// Basis points of a 1.0x payout multiplier
+pub const MULTIPLIER_BASIS_POINTS: u64 = 10_000;
+
+/// Payout multiplier (in basis points) a bet would get if the round were resolved in its favour
+/// right after it is placed: (prize pool + amount) / (side pool + amount)
+pub fn payout_multiplier_after_bet(round: &PredictionRound, amount: Amount, prediction: Prediction) -> u64 {
+    let side_pool = match prediction {
+        Prediction::Up => round.up_bets_pool,
+        Prediction::Down => round.down_bets_pool,
+    };
+    let prize_pool = u128::from(round.prize_pool.saturating_add(amount));
+    let side_pool = u128::from(side_pool.saturating_add(amount));
+    if side_pool == 0 {
+        return MULTIPLIER_BASIS_POINTS;
+    }
+    let multiplier = prize_pool.saturating_mul(u128::from(MULTIPLIER_BASIS_POINTS)) / side_pool;
+    u64::try_from(multiplier).unwrap_or(u64::MAX)
+}
+
+/// Check the optional slippage conditions of a bet against the round it would be placed in
+/// `now` and `deadline` are timestamps in microseconds
+pub fn check_bet_conditions(
+    round: &PredictionRound,
+    amount: Amount,
+    prediction: Prediction,
+    expected_round_id: Option<u64>,
+    min_payout_multiplier: Option<u64>,
+    deadline: Option<u64>,
+    now: u64,
+) -> Result<(), String> {
+    if let Some(deadline) = deadline {
+        if now > deadline {
+            return Err(format!("Bet deadline {} has passed", deadline));
+        }
+    }
+    if let Some(expected_round_id) = expected_round_id {
+        if round.id != expected_round_id {
+            return Err(format!("Expected round {} but round {} is active", expected_round_id, round.id));
+        }
+    }
+    if let Some(min_payout_multiplier) = min_payout_multiplier {
+        let multiplier = payout_multiplier_after_bet(round, amount, prediction);
+        if multiplier < min_payout_multiplier {
+            return Err(format!("Payout multiplier {} is below the minimum {}", multiplier, min_payout_multiplier));
+        }
+    }
+    Ok(())
+}
+
+/// Check the terms of a follow subscription before its budget is accepted
+pub fn check_follow(
+    follower: AccountOwner,
+    leader: AccountOwner,
+    ratio_bps: u64,
+    max_per_bet: Amount,
+    budget: Amount,
+) -> Result<(), String> {
+    if follower == leader {
+        return Err("Cannot follow yourself".to_string());
+    }
+    if ratio_bps == 0 || max_per_bet.is_zero() || budget.is_zero() {
+        return Err("Follow ratio, max per bet and budget must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// Parlay payout multipliers (basis points) by number of winning legs, used when none are configured
+pub const DEFAULT_PARLAY_MULTIPLIERS: [u64; 5] = [19_000, 36_000, 68_000, 130_000, 250_000];
+
+/// Payout of a settled parlay with `winning_legs` winning legs and no losing leg
+/// Void legs (ties) are dropped: the parlay pays the multiplier of its winning legs,
+/// and a parlay whose legs are all void gets its stake back
+pub fn parlay_payout(amount: Amount, winning_legs: usize, multipliers: &[u64]) -> Amount {
+    if winning_legs == 0 {
+        return amount;
+    }
+    let multiplier = multipliers.get(winning_legs - 1).copied().unwrap_or(MULTIPLIER_BASIS_POINTS);
+    let payout = u128::from(amount).saturating_mul(u128::from(multiplier)) / u128::from(MULTIPLIER_BASIS_POINTS);
+    Amount::from_attos(payout)
+}
+
 // Rounds Application ABI
 pub struct RoundsAbi;
 
@@ -83,6 +297,34 @@ impl ServiceAbi for RoundsAbi {
 pub struct RoundsParameters {
     pub native_app_id: ::linera_sdk::linera_base_types::ApplicationId,
     pub leaderboard_app_id: ::linera_sdk::linera_base_types::ApplicationId,
+    /// Fungible token app (standard fungible ABI) used as the stake currency of this market
+    /// If None, bets are placed in Native tokens
+    #[serde(default)]
+    pub currency_app_id: Option<::linera_sdk::linera_base_types::ApplicationId>,
+    /// Parlay payout multipliers (basis points) by number of winning legs, from 1
+    /// Its length is the largest number of legs a parlay can have; empty uses `DEFAULT_PARLAY_MULTIPLIERS`
+    #[serde(default)]
+    pub parlay_multipliers: Vec<u64>,
+    /// Owner allowed to withdraw the parlay pool funds that no open parlay relies on
+    /// If None, funds added to the parlay pool stay there for good
+    #[serde(default)]
+    pub parlay_pool_operator: Option<AccountOwner>,
+}
+
+impl RoundsParameters {
+    /// The fungible token app stakes and payouts of this market are made in
+    pub fn currency(&self) -> ApplicationId {
+        self.currency_app_id.unwrap_or(self.native_app_id)
+    }
+    
+    /// The configured parlay multiplier table
+    pub fn parlay_multipliers(&self) -> Vec<u64> {
+        if self.parlay_multipliers.is_empty() {
+            DEFAULT_PARLAY_MULTIPLIERS.to_vec()
+        } else {
+            self.parlay_multipliers.clone()
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -96,18 +338,69 @@ pub enum RoundsOperation {
     ResolveRound { resolution_price: Amount },
     
     // Betting operations (called by NativeFungible app)
-    /// Place a bet in the active round
+    /// Place a bet in the active round whose stake is already in the Microbetreal escrow (called by Microbetreal)
+    /// The bet is rejected if the active round is not `expected_round_id`, the payout multiplier
+    /// (basis points, 10_000 = 1.0x) would fall below `min_payout_multiplier`, or `deadline` (micros) has passed
     PlaceBet {
         owner: AccountOwner,
         amount: Amount,
         prediction: Prediction,
         source_chain_id: Option<String>, // For cross-chain attribution
+        #[serde(default)]
+        expected_round_id: Option<u64>,
+        #[serde(default)]
+        min_payout_multiplier: Option<u64>,
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Queue a bet to be placed as soon as the next round opens
+    /// (used for cross-chain bets that arrive while no round is active, called by Microbetreal)
+    QueueBet {
+        owner: AccountOwner,
+        amount: Amount,
+        prediction: Prediction,
+        source_chain_id: Option<String>,
+    },
+    /// Register an auto-bet strategy whose budget is already in the Microbetreal escrow (called by Microbetreal)
+    /// Strategies bet once per round until the budget or the loss limit is exhausted; Martingale and FollowLastResult
+    /// strategies bet as soon as their previous bet is resolved, the others when the round opens
+    RegisterStrategy {
+        owner: AccountOwner,
+        config: AutoBetConfig,
+        source_chain_id: Option<String>,
+    },
+    /// Stop a strategy and return what is left of its budget (called by Microbetreal, which pays the refund)
+    CancelStrategy { owner: AccountOwner, strategy_id: u64 },
+    /// Copy every `PlaceBet` of `leader` into bets of `follower` on the same side (called by Microbetreal)
+    /// Each copy stakes `ratio_bps` of the leader's amount, capped by `max_per_bet` and the remaining budget
+    Follow {
+        follower: AccountOwner,
+        leader: AccountOwner,
+        ratio_bps: u64,
+        max_per_bet: Amount,
+        budget: Amount,
+        source_chain_id: Option<String>,
+    },
+    /// Stop following and return what is left of the budget (called by Microbetreal, which pays the refund)
+    Unfollow { follower: AccountOwner, follow_id: u64 },
+    /// Predict the next `predictions.len()` rounds, starting with the active one, with a single stake (called by Microbetreal)
+    /// Settled leg by leg as the rounds resolve: a losing leg loses the stake, a tied round voids its leg
+    PlaceParlay {
+        owner: AccountOwner,
+        amount: Amount,
+        predictions: Vec<Prediction>,
+        source_chain_id: Option<String>,
     },
+    /// Add funds to the pool parlay winnings are paid from (called by Microbetreal)
+    FundParlayPool { amount: Amount },
+    /// Withdraw parlay pool funds not reserved for open parlays to the operator's account on this chain
+    /// (called by the configured parlay pool operator directly)
+    WithdrawParlayPool { amount: Amount },
     /// Claim winnings from a resolved round (called by user directly)
     ClaimWinnings { round_id: u64 },
     
     // Configuration operations
-    /// Set the Microbetreal app ID (called after deployment)
+    /// Set the Microbetreal app ID (called once after deployment, it cannot be changed afterwards)
     SetMicrobetAppId { microbet_app_id: String },
     /// Set the chain ID where Leaderboard app is deployed (for cross-chain updates)
     /// If None, leaderboard is on the same chain as rounds
@@ -124,6 +417,8 @@ pub enum RoundsOperation {
     GetActiveBets,
     /// Get winners for a resolved round
     GetRoundWinners { round_id: u64 },
+    /// Get the fungible token app used as the stake currency
+    GetCurrency,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -137,6 +432,32 @@ pub enum RoundsResponse {
     RoundWinners(Vec<RoundWinnerInfo>),
     // Add Winners response for ResolveRound to return winners list
     Winners(Vec<RoundWinnerInfo>),
+    /// Receipt for a placed bet
+    BetReceipt(BetReceipt),
+    /// Fungible token app used as the stake currency
+    Currency(ApplicationId),
+    /// ID of a registered auto-bet strategy
+    StrategyId(u64),
+    /// Unspent budget of a cancelled strategy, to be refunded to its owner
+    StrategyRefund {
+        owner: AccountOwner,
+        amount: Amount,
+        source_chain_id: Option<String>,
+    },
+    /// ID of a follow subscription
+    FollowId(u64),
+    /// Unspent budget of a stopped follow subscription, to be refunded to the follower
+    FollowRefund {
+        follower: AccountOwner,
+        amount: Amount,
+        source_chain_id: Option<String>,
+    },
+    /// ID of a placed parlay
+    ParlayId(u64),
+    /// A parlay was not placed; its stake has to be returned by the caller
+    ParlayRejected(String),
+    /// A follow subscription was not added; its budget has to be returned by the caller
+    FollowRejected(String),
 }
 
 // Message for cross-application communication
@@ -152,3 +473,33 @@ pub enum Message {
         amount: Amount,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parlay_payout() {
+        let stake = Amount::from_tokens(10);
+        
+        // All legs void: the stake is returned
+        assert_eq!(parlay_payout(stake, 0, &DEFAULT_PARLAY_MULTIPLIERS), stake);
+        // Winning legs pay their multiplier
+        assert_eq!(parlay_payout(stake, 1, &DEFAULT_PARLAY_MULTIPLIERS), Amount::from_tokens(19));
+        assert_eq!(parlay_payout(stake, 3, &DEFAULT_PARLAY_MULTIPLIERS), Amount::from_tokens(68));
+        assert_eq!(parlay_payout(stake, 5, &DEFAULT_PARLAY_MULTIPLIERS), Amount::from_tokens(250));
+        // Leg counts without a configured multiplier pay 1x
+        assert_eq!(parlay_payout(stake, 6, &DEFAULT_PARLAY_MULTIPLIERS), stake);
+        assert_eq!(parlay_payout(stake, 2, &[]), stake);
+    }
+
+    #[test]
+    fn test_parlay_payout_overflow() {
+        // 50 tokens with 18 decimals at 25x
+        let stake = Amount::from_attos(50_000_000_000_000_000_000);
+        
+        let payout = parlay_payout(stake, 5, &DEFAULT_PARLAY_MULTIPLIERS);
+        
+        assert_eq!(payout, Amount::from_attos(1_250_000_000_000_000_000_000), "Parlay payout overflowed!");
+    }
+}
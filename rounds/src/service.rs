@@ -9,15 +9,19 @@ use std::sync::Arc;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    abis::fungible::FungibleTokenAbi,
+    linera_base_types::{AccountOwner, Amount, ApplicationId, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
 use rounds::{
     RoundsAbi, RoundsOperation, Prediction, 
     PredictionRound as LibPredictionRound, RoundStatus as LibRoundStatus, 
-    ActiveBetInfo as LibActiveBetInfo, RoundWinnerInfo as LibRoundWinnerInfo
+    ActiveBetInfo as LibActiveBetInfo, RoundWinnerInfo as LibRoundWinnerInfo,
+    QueuedBetInfo as LibQueuedBetInfo, AutoBetStrategyInfo, FollowInfo, Solvency,
+    ParlayInfo, ParlayLegInfo, ParlayPoolInfo
 };
+use native_fungible_abi::ExtendedNativeFungibleTokenAbi;
 use self::state::{RoundsState, PredictionRound};
 
 linera_sdk::service!(RoundsService);
@@ -32,7 +36,7 @@ impl WithServiceAbi for RoundsService {
 }
 
 impl Service for RoundsService {
-    type Parameters = ();
+    type Parameters = rounds::RoundsParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = RoundsState::load(runtime.root_view_storage_context())
@@ -85,6 +89,34 @@ struct QueryRoot {
 
 #[Object]
 impl QueryRoot {
+    /// Get the fungible token app used as the stake currency of this market
+    async fn currency_app_id(&self) -> String {
+        self.runtime.application_parameters().currency().to_string()
+    }
+    
+    /// Compare what this market can pay out of the Microbetreal escrow with the stakes it still owes
+    /// The escrow account also holds the stakes of other games, so the market can pay at most
+    /// its own credit in Microbetreal's ledger, and only as long as the account holds the tokens
+    async fn solvency(&self) -> Option<Solvency> {
+        let state = RoundsState::load(self.storage_context.clone()).await.ok()?;
+        let liabilities = state.outstanding_liabilities();
+        
+        let microbet_app_id = *state.microbet_app_id.get();
+        let escrow_owner = microbet_app_id.map(|app_id| AccountOwner::from(app_id.forget_abi()));
+        let escrow_balance = escrow_owner.and_then(|owner| self.token_balance(owner));
+        let escrow_credit = microbet_app_id.and_then(|app_id| self.escrow_credit(app_id));
+        let payable = escrow_balance.zip(escrow_credit).map(|(balance, credit)| balance.min(credit));
+        
+        Some(Solvency {
+            escrow_owner,
+            escrow_balance,
+            escrow_credit,
+            liabilities,
+            surplus: payable.map(|payable| payable.saturating_sub(liabilities)),
+            is_solvent: payable.map(|payable| payable >= liabilities),
+        })
+    }
+    
     /// Get the active round
     async fn active_round(&self) -> Option<LibPredictionRound> {
         // Load a fresh state to query the active round
@@ -232,6 +264,124 @@ impl QueryRoot {
         }
     }
     
+    /// Get bets waiting for the next round to open
+    async fn queued_bets(&self) -> Vec<LibQueuedBetInfo> {
+        match RoundsState::load(self.storage_context.clone()).await {
+            Ok(state) => {
+                state.queued_bets.get().iter().map(|bet| {
+                    LibQueuedBetInfo {
+                        owner: bet.owner,
+                        amount: bet.amount,
+                        prediction: match bet.prediction {
+                            self::state::Prediction::Up => Prediction::Up,
+                            self::state::Prediction::Down => Prediction::Down,
+                        },
+                        source_chain_id: bet.source_chain_id.clone(),
+                    }
+                }).collect()
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    /// Get the auto-bet strategies, optionally only those of one owner
+    async fn strategies(&self, owner: Option<AccountOwner>) -> Vec<AutoBetStrategyInfo> {
+        match RoundsState::load(self.storage_context.clone()).await {
+            Ok(state) => {
+                state.get_strategies().await.unwrap_or_default().into_iter()
+                    .filter(|strategy| owner.is_none() || owner == Some(strategy.owner))
+                    .map(|strategy| AutoBetStrategyInfo {
+                        id: strategy.id,
+                        owner: strategy.owner,
+                        kind: strategy.kind,
+                        prediction: match strategy.prediction {
+                            self::state::Prediction::Up => Prediction::Up,
+                            self::state::Prediction::Down => Prediction::Down,
+                        },
+                        base_amount: strategy.base_amount,
+                        max_amount: strategy.max_amount,
+                        budget: strategy.budget,
+                        loss_limit: strategy.loss_limit,
+                        next_amount: strategy.next_amount,
+                        net_loss: strategy.net_loss(),
+                        total_lost: strategy.total_lost,
+                        total_won: strategy.total_won,
+                        pending_bets: strategy.pending_bets.len() as u64,
+                        active: strategy.active,
+                        source_chain_id: strategy.source_chain_id,
+                    })
+                    .collect()
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    /// Get follow subscriptions, optionally filtered by leader and/or follower
+    async fn follows(&self, leader: Option<AccountOwner>, follower: Option<AccountOwner>) -> Vec<FollowInfo> {
+        match RoundsState::load(self.storage_context.clone()).await {
+            Ok(state) => {
+                state.get_follows().await.unwrap_or_default().into_iter()
+                    .filter(|follow| leader.is_none() || leader == Some(follow.leader))
+                    .filter(|follow| follower.is_none() || follower == Some(follow.follower))
+                    .map(|follow| FollowInfo {
+                        id: follow.id,
+                        follower: follow.follower,
+                        leader: follow.leader,
+                        ratio_bps: follow.ratio_bps,
+                        max_per_bet: follow.max_per_bet,
+                        budget: follow.budget,
+                        copied_bets: follow.copied_bets,
+                        copied_amount: follow.copied_amount,
+                        active: follow.active,
+                        source_chain_id: follow.source_chain_id,
+                    })
+                    .collect()
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    /// Get parlays, optionally filtered by owner
+    async fn parlays(&self, owner: Option<AccountOwner>) -> Vec<ParlayInfo> {
+        match RoundsState::load(self.storage_context.clone()).await {
+            Ok(state) => {
+                state.get_parlays().await.unwrap_or_default().into_iter()
+                    .filter(|parlay| owner.is_none() || owner == Some(parlay.owner))
+                    .map(|parlay| ParlayInfo {
+                        id: parlay.id,
+                        owner: parlay.owner,
+                        amount: parlay.amount,
+                        legs: parlay.legs.into_iter()
+                            .map(|leg| ParlayLegInfo {
+                                round_id: leg.round_id,
+                                prediction: match leg.prediction {
+                                    self::state::Prediction::Up => Prediction::Up,
+                                    self::state::Prediction::Down => Prediction::Down,
+                                },
+                                outcome: leg.outcome,
+                            })
+                            .collect(),
+                        status: parlay.status,
+                        max_payout: parlay.max_payout,
+                        payout: parlay.payout,
+                        source_chain_id: parlay.source_chain_id,
+                    })
+                    .collect()
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    /// Get the parlay pool and the multiplier table
+    async fn parlay_pool(&self) -> Option<ParlayPoolInfo> {
+        let state = RoundsState::load(self.storage_context.clone()).await.ok()?;
+        Some(ParlayPoolInfo {
+            balance: *state.parlay_pool.get(),
+            reserved: *state.parlay_reserved.get(),
+            multipliers: self.runtime.application_parameters().parlay_multipliers(),
+        })
+    }
+    
     /// Get winners for a resolved round
     async fn round_winners(&self, round_id: u64) -> Vec<LibRoundWinnerInfo> {
         // Load a fresh state to query round winners
@@ -256,6 +406,30 @@ impl QueryRoot {
     }
 }
 
+impl QueryRoot {
+    /// Tokens `owner` holds in the market currency on this chain
+    fn token_balance(&self, owner: AccountOwner) -> Option<Amount> {
+        let currency = self.runtime.application_parameters().currency();
+        let request = Request::new(format!("query {{ accounts {{ entry(key: \"{}\") {{ value }} }} }}", owner));
+        let response = self.runtime.query_application(currency.with_abi::<FungibleTokenAbi>(), &request);
+        let json = response.data.into_json().ok()?;
+        json["accounts"]["entry"]["value"].as_str()?.parse::<Amount>().ok()
+    }
+    
+    /// Stakes of this app in Microbetreal's escrow ledger, in the market currency on this chain
+    fn escrow_credit(&self, microbet_app_id: ApplicationId<ExtendedNativeFungibleTokenAbi>) -> Option<Amount> {
+        let currency = self.runtime.application_parameters().currency();
+        let request = Request::new(format!(
+            "query {{ gameEscrow(gameAppId: \"{}\", currencyAppId: \"{}\") }}",
+            self.runtime.application_id().forget_abi(),
+            currency
+        ));
+        let response = self.runtime.query_application(microbet_app_id, &request);
+        let json = response.data.into_json().ok()?;
+        json["gameEscrow"].as_str()?.parse::<Amount>().ok()
+    }
+}
+
 struct MutationRoot {
     runtime: Arc<ServiceRuntime<RoundsService>>,
 }
@@ -278,6 +452,13 @@ impl MutationRoot {
         }
     }
 
+    /// Withdraw parlay pool funds not reserved for open parlays (parlay pool operator only)
+    async fn withdraw_parlay_pool(&self, amount: String) -> String {
+        let amount = amount.parse::<Amount>().unwrap_or_default();
+        self.runtime.schedule_operation(&RoundsOperation::WithdrawParlayPool { amount });
+        "WithdrawParlayPool operation scheduled".to_string()
+    }
+
     /// Create a new prediction round
     async fn create_round(&self) -> String {
         self.runtime.schedule_operation(&RoundsOperation::CreateRound);
@@ -297,15 +478,5 @@ impl MutationRoot {
         self.runtime.schedule_operation(&RoundsOperation::ResolveRound { resolution_price: amount });
         "ResolveRound operation scheduled - will call NativeFungible for reward distribution".to_string()
     }
-    
-    /// Place a bet in the active round (typically called via cross-app call from NativeFungible)
-    async fn place_bet(&self, owner: AccountOwner, amount: String, prediction: Prediction, source_chain_id: Option<String>) -> String {
-        self.runtime.schedule_operation(&RoundsOperation::PlaceBet {
-            owner,
-            amount: amount.parse::<Amount>().unwrap_or_default(),
-            prediction,
-            source_chain_id,
-        });
-        "PlaceBet operation scheduled".to_string()
-    }
+
 }
@@ -7,6 +7,10 @@ use serde::{Deserialize, Serialize};
 use async_graphql::SimpleObject;
 use num_bigint::BigUint;
 use num_traits::cast::ToPrimitive;
+use rounds::{StrategyKind, LegOutcome, ParlayStatus};
+
+/// Number of active follow subscriptions a leader can have, so one bet cannot fan out without bound
+const MAX_FOLLOWERS_PER_LEADER: usize = 100;
 
 /// Calculate winnings proportionally based on bet amount
 /// Returns bet_amount + (bet_amount / winner_pool) * total_prize_pool
@@ -65,6 +69,43 @@ pub struct RoundsState {
     pub closed_bets: MapView<(u64, AccountOwner), PredictionBet>,
     /// Bets placed in resolved rounds (awaiting claim)
     pub resolved_bets: MapView<(u64, AccountOwner), PredictionBet>,
+    /// Bets waiting for the next round to open (placed on create/close round)
+    pub queued_bets: RegisterView<Vec<QueuedBet>>,
+    /// Counter for generating unique strategy IDs
+    pub strategy_counter: RegisterView<u64>,
+    /// Auto-bet strategies, betting every time a round opens
+    pub strategies: MapView<u64, AutoBetStrategy>,
+    /// Result of the last resolved round (followed by `FollowLastResult` strategies)
+    pub last_result: RegisterView<Option<Prediction>>,
+    /// Counter for generating unique follow subscription IDs
+    pub follow_counter: RegisterView<u64>,
+    /// Follow subscriptions copying the bets of a leader
+    pub follows: MapView<u64, FollowSubscription>,
+    /// Follow subscription IDs of each leader
+    pub followers: MapView<AccountOwner, Vec<u64>>,
+    /// Counter for generating unique parlay IDs
+    pub parlay_counter: RegisterView<u64>,
+    /// All parlays
+    pub parlays: MapView<u64, Parlay>,
+    /// IDs of parlays with legs left to settle
+    pub open_parlays: RegisterView<Vec<u64>>,
+    /// Funds parlay winnings are paid from (house bankroll plus the stakes of lost parlays)
+    pub parlay_pool: RegisterView<Amount>,
+    /// Part of the parlay pool promised to open parlays
+    pub parlay_reserved: RegisterView<Amount>,
+    /// Payouts of settled parlays waiting to be sent: (owner, amount, source_chain_id)
+    pub parlay_payouts: RegisterView<Vec<(AccountOwner, Amount, Option<String>)>>,
+    /// Queued bets that could not be placed, waiting to be refunded: (owner, amount, source_chain_id)
+    pub bet_refunds: RegisterView<Vec<(AccountOwner, Amount, Option<String>)>>,
+    /// IDs of strategies that are active, hold a budget or have unsettled bets
+    pub live_strategies: RegisterView<Vec<u64>>,
+    /// Counter for bet sequence numbers (returned in bet receipts)
+    pub bet_counter: RegisterView<u64>,
+    /// Queued bets placed or refunded when a round opened, waiting to be reported to Microbetreal:
+    /// (bet, Some((round_id, total_stake, sequence)) if placed or None if refunded)
+    pub queued_bet_outcomes: RegisterView<Vec<(QueuedBet, Option<(u64, Amount, u64)>)>>,
+    /// Running total of what the escrow owes to bettors (see `outstanding_liabilities`)
+    pub liabilities: RegisterView<Amount>,
 }
 
 /// A prediction round for the Up/Down game
@@ -103,6 +144,92 @@ pub struct PredictionBet {
     pub source_chain_id: Option<String>, // Add source chain ID for cross-chain bets
 }
 
+/// A bet that arrived while no round was active
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct QueuedBet {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub prediction: Prediction,
+    pub source_chain_id: Option<String>,
+}
+
+/// A standing auto-bet strategy and its progress
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AutoBetStrategy {
+    pub id: u64,
+    pub owner: AccountOwner,
+    pub kind: StrategyKind,
+    pub prediction: Prediction,
+    pub base_amount: Amount,
+    pub max_amount: Amount,
+    pub budget: Amount,                  // Left for future bets
+    pub loss_limit: Option<Amount>,
+    pub next_amount: Amount,             // Stake of the next Martingale bet
+    pub total_lost: Amount,              // Stakes of settled bets that lost
+    pub total_won: Amount,               // Profits of settled bets that won
+    pub last_prediction: Option<Prediction>,
+    pub pending_bets: Vec<StrategyBet>,  // Bets in rounds that are not resolved yet
+    pub active: bool,
+    pub source_chain_id: Option<String>,
+}
+
+impl AutoBetStrategy {
+    /// Whether the strategy still bets, holds funds or waits for a settlement
+    fn is_live(&self) -> bool {
+        self.active || !self.budget.is_zero() || !self.pending_bets.is_empty()
+    }
+    
+    /// Losses minus profits of settled bets, zero while the strategy is ahead
+    pub fn net_loss(&self) -> Amount {
+        self.total_lost.saturating_sub(self.total_won)
+    }
+}
+
+/// A follower copying the bets of a leader
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct FollowSubscription {
+    pub id: u64,
+    pub follower: AccountOwner,
+    pub leader: AccountOwner,
+    pub ratio_bps: u64,
+    pub max_per_bet: Amount,
+    pub budget: Amount,
+    pub copied_bets: u64,
+    pub copied_amount: Amount,
+    pub active: bool,
+    pub source_chain_id: Option<String>,
+}
+
+/// A single stake predicting several consecutive rounds
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Parlay {
+    pub id: u64,
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub legs: Vec<ParlayLeg>,
+    pub multipliers: Vec<u64>,   // Multiplier table at the time the parlay was placed
+    pub status: ParlayStatus,
+    pub max_payout: Amount,
+    pub payout: Option<Amount>,
+    pub source_chain_id: Option<String>,
+}
+
+/// One round predicted by a parlay
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ParlayLeg {
+    pub round_id: u64,
+    pub prediction: Prediction,
+    pub outcome: LegOutcome,
+}
+
+/// A bet placed by a strategy
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct StrategyBet {
+    pub round_id: u64,
+    pub prediction: Prediction,
+    pub amount: Amount,
+}
+
 /// Prediction direction for the Up/Down game
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
 pub enum Prediction {
@@ -148,6 +275,9 @@ impl RoundsState {
                 .map_err(|e: ViewError| format!("Failed to remove active bet: {:?}", e))?;
         }
         
+        self.place_queued_bets().await;
+        self.place_strategy_bets().await?;
+        
         Ok(round_id)
     }
     
@@ -257,6 +387,9 @@ impl RoundsState {
                     .map_err(|e: ViewError| format!("Failed to remove active bet: {:?}", e))?;
             }
             
+            self.place_queued_bets().await;
+            self.place_strategy_bets().await?;
+            
             Ok(new_round_id)
         } else {
             Err("No active round to close".to_string())
@@ -326,12 +459,20 @@ impl RoundsState {
         let mut results = Vec::new();
         
         // Calculate total prize pool and winner pool for calculations
+        // The pool is paid out with the results, or kept by the house if nobody won
         let total_prize_pool = round.prize_pool;
+        self.release_liabilities(total_prize_pool);
         let winner_pool = match result {
             Some(Prediction::Up) => round.up_bets_pool,
             Some(Prediction::Down) => round.down_bets_pool,
             None => Amount::ZERO,
         };
+        
+        self.last_result.set(result);
+        self.settle_strategy_bets(round_id, result, winner_pool, total_prize_pool).await?;
+        self.settle_parlay_legs(round_id, result).await?;
+        // Strategies that were waiting for this result bet into the round that is already open
+        self.place_strategy_bets().await?;
 
         // Reuse bets_to_move (which contains all bets for this round) to generate results
         for (_, bet) in &bets_to_move {
@@ -390,7 +531,8 @@ impl RoundsState {
 
     
     /// Place a bet in the active round
-    pub async fn place_bet(&mut self, owner: AccountOwner, amount: Amount, prediction: Prediction, source_chain_id: Option<String>) -> Result<(), String> {
+    /// Returns: (round_id, cumulative stake of the owner on this side, bet sequence number)
+    pub async fn place_bet(&mut self, owner: AccountOwner, amount: Amount, prediction: Prediction, source_chain_id: Option<String>) -> Result<(u64, Amount, u64), String> {
         let round_id_opt = self.active_round.get();
         
         if let Some(round_id) = *round_id_opt {
@@ -449,6 +591,11 @@ impl RoundsState {
                 }
             };
             
+            let total_stake = match prediction {
+                Prediction::Up => bet.amount_up,
+                Prediction::Down => bet.amount_down,
+            };
+            
             self.active_bets.insert(&owner, bet)
                 .map_err(|e: ViewError| format!("Failed to place bet: {:?}", e))?;
             
@@ -462,18 +609,519 @@ impl RoundsState {
                 },
             }
             round.prize_pool = round.prize_pool.saturating_add(amount);
+            self.add_liabilities(amount);
             
             // Save updated round
             self.rounds.insert(&round_id, round.clone())
                 .map_err(|e: ViewError| format!("Failed to update round statistics: {:?}", e))?;
+            
+            let sequence = *self.bet_counter.get() + 1;
+            self.bet_counter.set(sequence);
+            
+            Ok((round_id, total_stake, sequence))
         } else {
-            return Err("No active round".to_string());
+            Err("No active round".to_string())
         }
-
+    }
+    
+    /// Queue a bet to be placed as soon as the next round opens
+    pub fn queue_bet(&mut self, owner: AccountOwner, amount: Amount, prediction: Prediction, source_chain_id: Option<String>) {
+        self.add_liabilities(amount);
+        self.queued_bets.get_mut().push(QueuedBet {
+            owner,
+            amount,
+            prediction,
+            source_chain_id,
+        });
+    }
+    
+    /// Place all queued bets into the freshly opened active round
+    /// A bet that cannot be placed is set aside for a refund instead of failing the round transition
+    async fn place_queued_bets(&mut self) {
+        let queued = std::mem::take(self.queued_bets.get_mut());
+        for bet in queued {
+            // Placed bets are counted in the round pool, refunded ones are paid out
+            self.release_liabilities(bet.amount);
+            match self.place_bet(bet.owner, bet.amount, bet.prediction, bet.source_chain_id.clone()).await {
+                Ok(placed) => self.queued_bet_outcomes.get_mut().push((bet, Some(placed))),
+                Err(e) => {
+                    eprintln!("Failed to place queued bet of {:?}: {}", bet.owner, e);
+                    self.bet_refunds.get_mut().push((bet.owner, bet.amount, bet.source_chain_id.clone()));
+                    self.queued_bet_outcomes.get_mut().push((bet, None));
+                }
+            }
+        }
+    }
+    
+    /// Take the queued bets that could not be placed so they can be refunded
+    pub fn take_bet_refunds(&mut self) -> Vec<(AccountOwner, Amount, Option<String>)> {
+        std::mem::take(self.bet_refunds.get_mut())
+    }
+    
+    /// Take the outcomes of the queued bets so they can be reported to Microbetreal
+    pub fn take_queued_bet_outcomes(&mut self) -> Vec<(QueuedBet, Option<(u64, Amount, u64)>)> {
+        std::mem::take(self.queued_bet_outcomes.get_mut())
+    }
+    
+    /// Register an auto-bet strategy and return its ID
+    pub fn add_strategy(&mut self, mut strategy: AutoBetStrategy) -> Result<u64, String> {
+        if strategy.base_amount.is_zero() {
+            return Err("Strategy bet amount must be positive".to_string());
+        }
+        if strategy.max_amount < strategy.base_amount {
+            return Err("Strategy max amount is below its base amount".to_string());
+        }
+        if strategy.budget < strategy.base_amount {
+            return Err("Strategy budget does not cover a single bet".to_string());
+        }
+        let strategy_id = *self.strategy_counter.get() + 1;
+        self.strategy_counter.set(strategy_id);
+        strategy.id = strategy_id;
+        self.add_liabilities(strategy.budget);
+        self.strategies.insert(&strategy_id, strategy)
+            .map_err(|e: ViewError| format!("Failed to store strategy: {:?}", e))?;
+        self.live_strategies.get_mut().push(strategy_id);
+        Ok(strategy_id)
+    }
+    
+    /// Stop a strategy of `owner` and take its unspent budget
+    /// Returns: (unspent budget, source chain ID of the owner)
+    pub async fn cancel_strategy(&mut self, owner: AccountOwner, strategy_id: u64) -> Result<(Amount, Option<String>), String> {
+        let mut strategy = self.strategies.get(&strategy_id).await
+            .map_err(|e: ViewError| format!("Failed to get strategy: {:?}", e))?
+            .ok_or("Strategy not found")?;
+        if strategy.owner != owner {
+            return Err("Strategy belongs to another owner".to_string());
+        }
+        let refund = strategy.budget;
+        strategy.budget = Amount::ZERO;
+        strategy.active = false;
+        self.release_liabilities(refund);
+        let source_chain_id = strategy.source_chain_id.clone();
+        if !strategy.is_live() {
+            self.live_strategies.get_mut().retain(|id| *id != strategy_id);
+        }
+        self.strategies.insert(&strategy_id, strategy)
+            .map_err(|e: ViewError| format!("Failed to store strategy: {:?}", e))?;
+        Ok((refund, source_chain_id))
+    }
+    
+    /// Take the unspent budget of strategies that stopped on their own
+    /// Returns: Vec<(owner, unspent budget, source_chain_id)>
+    pub async fn take_strategy_refunds(&mut self) -> Result<Vec<(AccountOwner, Amount, Option<String>)>, String> {
+        let mut refunds = Vec::new();
+        let mut live = Vec::new();
+        for mut strategy in self.get_live_strategies().await? {
+            if !strategy.active && !strategy.budget.is_zero() {
+                refunds.push((strategy.owner, strategy.budget, strategy.source_chain_id.clone()));
+                self.release_liabilities(strategy.budget);
+                strategy.budget = Amount::ZERO;
+                let strategy_id = strategy.id;
+                self.strategies.insert(&strategy_id, strategy.clone())
+                    .map_err(|e: ViewError| format!("Failed to store strategy: {:?}", e))?;
+            }
+            if strategy.is_live() {
+                live.push(strategy.id);
+            }
+        }
+        self.live_strategies.set(live);
+        Ok(refunds)
+    }
+    
+    /// Get all auto-bet strategies
+    pub async fn get_strategies(&self) -> Result<Vec<AutoBetStrategy>, String> {
+        let mut strategies = Vec::new();
+        let indices = self.strategies.indices().await
+            .map_err(|e: ViewError| format!("Failed to get strategy indices: {:?}", e))?;
+        for strategy_id in indices {
+            if let Some(strategy) = self.strategies.get(&strategy_id).await
+                .map_err(|e: ViewError| format!("Failed to get strategy: {:?}", e))? {
+                strategies.push(strategy);
+            }
+        }
+        Ok(strategies)
+    }
+    
+    /// Get the strategies listed in `live_strategies`
+    async fn get_live_strategies(&self) -> Result<Vec<AutoBetStrategy>, String> {
+        let mut strategies = Vec::new();
+        for strategy_id in self.live_strategies.get() {
+            if let Some(strategy) = self.strategies.get(strategy_id).await
+                .map_err(|e: ViewError| format!("Failed to get strategy: {:?}", e))? {
+                strategies.push(strategy);
+            }
+        }
+        Ok(strategies)
+    }
+    
+    /// Place the bet of every active strategy that has not bet into the active round yet
+    /// Martingale and FollowLastResult strategies wait until their last bet is settled, since its outcome picks the next bet
+    /// A strategy stops once its budget cannot cover the next bet, its loss limit is reached or its bet is rejected;
+    /// the budget of a stopped strategy is refunded through `take_strategy_refunds`
+    async fn place_strategy_bets(&mut self) -> Result<(), String> {
+        let Some(active_round_id) = *self.active_round.get() else {
+            return Ok(());
+        };
+        let last_result = *self.last_result.get();
+        for mut strategy in self.get_live_strategies().await? {
+            if !strategy.active || strategy.pending_bets.iter().any(|bet| bet.round_id == active_round_id) {
+                continue;
+            }
+            let waits_for_result = matches!(strategy.kind, StrategyKind::Martingale | StrategyKind::FollowLastResult);
+            if waits_for_result && !strategy.pending_bets.is_empty() {
+                continue;
+            }
+            
+            let amount = match strategy.kind {
+                StrategyKind::Martingale => strategy.next_amount,
+                _ => strategy.base_amount,
+            };
+            let loss_limit_reached = strategy.loss_limit.is_some_and(|limit| strategy.net_loss() >= limit);
+            if loss_limit_reached || amount > strategy.budget {
+                strategy.active = false;
+            } else {
+                let prediction = match strategy.kind {
+                    StrategyKind::FixedSide | StrategyKind::Martingale => strategy.prediction,
+                    StrategyKind::Alternate => match strategy.last_prediction {
+                        Some(Prediction::Up) => Prediction::Down,
+                        Some(Prediction::Down) => Prediction::Up,
+                        None => strategy.prediction,
+                    },
+                    StrategyKind::FollowLastResult => last_result.unwrap_or(strategy.prediction),
+                };
+                match self.place_bet(strategy.owner, amount, prediction, strategy.source_chain_id.clone()).await {
+                    Ok((round_id, _, _)) => {
+                        // The stake moves from the budget to the round pool
+                        self.release_liabilities(amount);
+                        strategy.budget = strategy.budget.saturating_sub(amount);
+                        strategy.last_prediction = Some(prediction);
+                        strategy.pending_bets.push(StrategyBet { round_id, prediction, amount });
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to place bet of strategy {}: {}", strategy.id, e);
+                        strategy.active = false;
+                    }
+                }
+            }
+            
+            let strategy_id = strategy.id;
+            self.strategies.insert(&strategy_id, strategy)
+                .map_err(|e: ViewError| format!("Failed to store strategy: {:?}", e))?;
+        }
+        Ok(())
+    }
+    
+    /// Subscribe a follower to the bets of a leader and return the subscription ID
+    pub async fn add_follow(&mut self, mut follow: FollowSubscription) -> Result<u64, String> {
+        rounds::check_follow(follow.follower, follow.leader, follow.ratio_bps, follow.max_per_bet, follow.budget)?;
+        let mut follow_ids = self.followers.get(&follow.leader).await
+            .map_err(|e: ViewError| format!("Failed to get followers: {:?}", e))?
+            .unwrap_or_default();
+        if follow_ids.len() >= MAX_FOLLOWERS_PER_LEADER {
+            return Err(format!("Leader already has {} followers", MAX_FOLLOWERS_PER_LEADER));
+        }
+        
+        let follow_id = *self.follow_counter.get() + 1;
+        self.follow_counter.set(follow_id);
+        follow.id = follow_id;
+        self.add_liabilities(follow.budget);
+        follow_ids.push(follow_id);
+        self.followers.insert(&follow.leader, follow_ids)
+            .map_err(|e: ViewError| format!("Failed to store followers: {:?}", e))?;
+        self.follows.insert(&follow_id, follow)
+            .map_err(|e: ViewError| format!("Failed to store follow: {:?}", e))?;
+        Ok(follow_id)
+    }
+    
+    /// Stop a follow subscription of `follower` and take its unspent budget
+    /// Returns: (unspent budget, source chain ID of the follower)
+    pub async fn remove_follow(&mut self, follower: AccountOwner, follow_id: u64) -> Result<(Amount, Option<String>), String> {
+        let mut follow = self.follows.get(&follow_id).await
+            .map_err(|e: ViewError| format!("Failed to get follow: {:?}", e))?
+            .ok_or("Follow not found")?;
+        if follow.follower != follower {
+            return Err("Follow belongs to another owner".to_string());
+        }
+        let refund = follow.budget;
+        follow.budget = Amount::ZERO;
+        follow.active = false;
+        self.release_liabilities(refund);
+        let source_chain_id = follow.source_chain_id.clone();
+        
+        let mut follow_ids = self.followers.get(&follow.leader).await
+            .map_err(|e: ViewError| format!("Failed to get followers: {:?}", e))?
+            .unwrap_or_default();
+        follow_ids.retain(|id| *id != follow_id);
+        self.followers.insert(&follow.leader, follow_ids)
+            .map_err(|e: ViewError| format!("Failed to store followers: {:?}", e))?;
+        self.follows.insert(&follow_id, follow)
+            .map_err(|e: ViewError| format!("Failed to store follow: {:?}", e))?;
+        Ok((refund, source_chain_id))
+    }
+    
+    /// Get all follow subscriptions
+    pub async fn get_follows(&self) -> Result<Vec<FollowSubscription>, String> {
+        let mut follows = Vec::new();
+        let indices = self.follows.indices().await
+            .map_err(|e: ViewError| format!("Failed to get follow indices: {:?}", e))?;
+        for follow_id in indices {
+            if let Some(follow) = self.follows.get(&follow_id).await
+                .map_err(|e: ViewError| format!("Failed to get follow: {:?}", e))? {
+                follows.push(follow);
+            }
+        }
+        Ok(follows)
+    }
+    
+    /// Copy a bet of `leader` into the active round for each of its followers
+    /// Copied bets are not copied again, so following chains do not cascade
+    pub async fn place_follower_bets(&mut self, leader: AccountOwner, amount: Amount, prediction: Prediction) -> Result<(), String> {
+        let follow_ids = self.followers.get(&leader).await
+            .map_err(|e: ViewError| format!("Failed to get followers: {:?}", e))?
+            .unwrap_or_default();
+        let mut exhausted = Vec::new();
+        for follow_id in follow_ids {
+            let Some(mut follow) = self.follows.get(&follow_id).await
+                .map_err(|e: ViewError| format!("Failed to get follow: {:?}", e))? else {
+                continue;
+            };
+            if !follow.active {
+                exhausted.push(follow_id);
+                continue;
+            }
+            
+            let proportional = u128::from(amount).saturating_mul(u128::from(follow.ratio_bps)) / u128::from(rounds::MULTIPLIER_BASIS_POINTS);
+            let copy_amount = Amount::from_attos(proportional).min(follow.max_per_bet).min(follow.budget);
+            if copy_amount.is_zero() {
+                continue;
+            }
+            
+            // One follower that cannot bet must not stop the copies of the others
+            if let Err(e) = self.place_bet(follow.follower, copy_amount, prediction, follow.source_chain_id.clone()).await {
+                eprintln!("Failed to copy bet for follow {}: {}", follow_id, e);
+                continue;
+            }
+            // The stake moves from the budget to the round pool
+            self.release_liabilities(copy_amount);
+            follow.budget = follow.budget.saturating_sub(copy_amount);
+            follow.copied_bets += 1;
+            follow.copied_amount = follow.copied_amount.saturating_add(copy_amount);
+            if follow.budget.is_zero() {
+                follow.active = false;
+                exhausted.push(follow_id);
+            }
+            self.follows.insert(&follow_id, follow)
+                .map_err(|e: ViewError| format!("Failed to store follow: {:?}", e))?;
+        }
+        
+        // Stop looking up subscriptions that will never copy again
+        if !exhausted.is_empty() {
+            let mut follow_ids = self.followers.get(&leader).await
+                .map_err(|e: ViewError| format!("Failed to get followers: {:?}", e))?
+                .unwrap_or_default();
+            follow_ids.retain(|id| !exhausted.contains(id));
+            self.followers.insert(&leader, follow_ids)
+                .map_err(|e: ViewError| format!("Failed to store followers: {:?}", e))?;
+        }
+        Ok(())
+    }
+    
+    /// Add funds to the parlay pool
+    pub fn fund_parlay_pool(&mut self, amount: Amount) {
+        let balance = self.parlay_pool.get().saturating_add(amount);
+        self.parlay_pool.set(balance);
+    }
+    
+    /// Take funds out of the parlay pool, leaving what open parlays may still win
+    pub fn withdraw_parlay_pool(&mut self, amount: Amount) -> Result<(), String> {
+        let available = self.parlay_pool.get().saturating_sub(*self.parlay_reserved.get());
+        if amount.is_zero() || amount > available {
+            return Err(format!("Can withdraw up to {} from the parlay pool", available));
+        }
+        let balance = self.parlay_pool.get().saturating_sub(amount);
+        self.parlay_pool.set(balance);
+        Ok(())
+    }
+    
+    /// Place a parlay on the active round and the rounds after it, one leg per prediction
+    /// The winnings of a full win are reserved from the parlay pool, so the parlay is rejected if the pool cannot cover them
+    pub async fn add_parlay(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+        predictions: Vec<Prediction>,
+        multipliers: Vec<u64>,
+        source_chain_id: Option<String>,
+    ) -> Result<u64, String> {
+        if amount.is_zero() {
+            return Err("Parlay amount must be positive".to_string());
+        }
+        if predictions.is_empty() || predictions.len() > multipliers.len() {
+            return Err(format!("A parlay needs between 1 and {} legs", multipliers.len()));
+        }
+        let first_round_id = (*self.active_round.get()).ok_or("No active round")?;
         
+        let max_payout = rounds::parlay_payout(amount, predictions.len(), &multipliers);
+        let max_winnings = max_payout.saturating_sub(amount);
+        let reserved = self.parlay_reserved.get().saturating_add(max_winnings);
+        if reserved > *self.parlay_pool.get() {
+            return Err("Parlay pool cannot cover the payout".to_string());
+        }
+        self.parlay_reserved.set(reserved);
+        self.add_liabilities(max_payout);
+        
+        let parlay_id = *self.parlay_counter.get() + 1;
+        self.parlay_counter.set(parlay_id);
+        let legs = predictions.into_iter().enumerate()
+            .map(|(index, prediction)| ParlayLeg {
+                round_id: first_round_id + index as u64,
+                prediction,
+                outcome: LegOutcome::Pending,
+            })
+            .collect();
+        let parlay = Parlay {
+            id: parlay_id,
+            owner,
+            amount,
+            legs,
+            multipliers,
+            status: ParlayStatus::Open,
+            max_payout,
+            payout: None,
+            source_chain_id,
+        };
+        self.parlays.insert(&parlay_id, parlay)
+            .map_err(|e: ViewError| format!("Failed to store parlay: {:?}", e))?;
+        let mut open_parlays = self.open_parlays.get().clone();
+        open_parlays.push(parlay_id);
+        self.open_parlays.set(open_parlays);
+        Ok(parlay_id)
+    }
+    
+    /// Get all parlays
+    pub async fn get_parlays(&self) -> Result<Vec<Parlay>, String> {
+        let mut parlays = Vec::new();
+        let indices = self.parlays.indices().await
+            .map_err(|e: ViewError| format!("Failed to get parlay indices: {:?}", e))?;
+        for parlay_id in indices {
+            if let Some(parlay) = self.parlays.get(&parlay_id).await
+                .map_err(|e: ViewError| format!("Failed to get parlay: {:?}", e))? {
+                parlays.push(parlay);
+            }
+        }
+        Ok(parlays)
+    }
+    
+    /// Take the payouts of settled parlays so they can be sent
+    pub fn take_parlay_payouts(&mut self) -> Vec<(AccountOwner, Amount, Option<String>)> {
+        std::mem::take(self.parlay_payouts.get_mut())
+    }
+    
+    /// Settle the parlay legs on a resolved round
+    /// A losing leg settles the parlay and moves its stake to the pool; once no leg is pending,
+    /// the parlay is paid from the pool with the multiplier of its winning legs (void legs are dropped)
+    async fn settle_parlay_legs(&mut self, round_id: u64, result: Option<Prediction>) -> Result<(), String> {
+        let mut still_open = Vec::new();
+        for parlay_id in self.open_parlays.get().clone() {
+            let Some(mut parlay) = self.parlays.get(&parlay_id).await
+                .map_err(|e: ViewError| format!("Failed to get parlay: {:?}", e))? else {
+                continue;
+            };
+            for leg in parlay.legs.iter_mut().filter(|leg| leg.round_id == round_id) {
+                leg.outcome = match result {
+                    None => LegOutcome::Void,
+                    Some(result) if result == leg.prediction => LegOutcome::Won,
+                    Some(_) => LegOutcome::Lost,
+                };
+            }
+            
+            let lost = parlay.legs.iter().any(|leg| leg.outcome == LegOutcome::Lost);
+            let pending = parlay.legs.iter().any(|leg| leg.outcome == LegOutcome::Pending);
+            if !lost && pending {
+                still_open.push(parlay_id);
+                continue;
+            }
+            
+            let max_winnings = parlay.max_payout.saturating_sub(parlay.amount);
+            let reserved = self.parlay_reserved.get().saturating_sub(max_winnings);
+            self.parlay_reserved.set(reserved);
+            // The payout is sent, or the stake goes to the pool, and the rest of the reservation returns to it
+            self.release_liabilities(parlay.max_payout);
+            if lost {
+                parlay.status = ParlayStatus::Lost;
+                parlay.payout = Some(Amount::ZERO);
+                self.fund_parlay_pool(parlay.amount);
+            } else {
+                let winning_legs = parlay.legs.iter().filter(|leg| leg.outcome == LegOutcome::Won).count();
+                let payout = rounds::parlay_payout(parlay.amount, winning_legs, &parlay.multipliers).min(parlay.max_payout);
+                let winnings = payout.saturating_sub(parlay.amount);
+                let balance = self.parlay_pool.get().saturating_sub(winnings);
+                self.parlay_pool.set(balance);
+                parlay.status = if winning_legs == 0 { ParlayStatus::Refunded } else { ParlayStatus::Won };
+                parlay.payout = Some(payout);
+                self.parlay_payouts.get_mut().push((parlay.owner, payout, parlay.source_chain_id.clone()));
+            }
+            self.parlays.insert(&parlay_id, parlay)
+                .map_err(|e: ViewError| format!("Failed to store parlay: {:?}", e))?;
+        }
+        self.open_parlays.set(still_open);
+        Ok(())
+    }
+    
+    /// Settle the strategy bets of a resolved round: track net losses and the next Martingale stake
+    async fn settle_strategy_bets(&mut self, round_id: u64, result: Option<Prediction>, winner_pool: Amount, total_prize_pool: Amount) -> Result<(), String> {
+        let mut live = Vec::new();
+        for mut strategy in self.get_live_strategies().await? {
+            let (settled, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut strategy.pending_bets)
+                .into_iter()
+                .partition(|bet| bet.round_id == round_id);
+            strategy.pending_bets = pending;
+            if strategy.is_live() {
+                live.push(strategy.id);
+            }
+            if settled.is_empty() {
+                continue;
+            }
+            
+            for bet in settled {
+                if result == Some(bet.prediction) {
+                    let winnings = calculate_winnings_proportional(bet.amount, winner_pool, total_prize_pool);
+                    strategy.total_won = strategy.total_won.saturating_add(winnings.saturating_sub(bet.amount));
+                    strategy.next_amount = strategy.base_amount;
+                } else {
+                    strategy.total_lost = strategy.total_lost.saturating_add(bet.amount);
+                    strategy.next_amount = bet.amount.saturating_add(bet.amount).min(strategy.max_amount);
+                }
+            }
+            
+            let strategy_id = strategy.id;
+            self.strategies.insert(&strategy_id, strategy)
+                .map_err(|e: ViewError| format!("Failed to store strategy: {:?}", e))?;
+        }
+        self.live_strategies.set(live);
         Ok(())
     }
     
+    /// Stakes the escrow still owes to bettors: pools of active and closed (unresolved) rounds,
+    /// queued bets, unspent strategy and follow budgets, and open parlays with their reserved winnings
+    /// Resolved rounds are paid out when they are resolved, so they carry no liability
+    /// Kept as a running total, updated wherever one of these amounts changes
+    pub fn outstanding_liabilities(&self) -> Amount {
+        *self.liabilities.get()
+    }
+    
+    /// Count funds the escrow now owes to bettors
+    fn add_liabilities(&mut self, amount: Amount) {
+        let total = self.liabilities.get().saturating_add(amount);
+        self.liabilities.set(total);
+    }
+    
+    /// Stop counting funds that were paid out, moved elsewhere in the total or kept by the house
+    fn release_liabilities(&mut self, amount: Amount) {
+        let total = self.liabilities.get().saturating_sub(amount);
+        self.liabilities.set(total);
+    }
+    
     /// Get the active round ID
     pub async fn get_active_round(&self) -> Result<Option<u64>, String> {
         Ok(*self.active_round.get())